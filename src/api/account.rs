@@ -1,7 +1,17 @@
 use crate::{
     AccountValidator, QueryHelper,
-    api::{amount::amount_from_uccd, error::ApiResult},
-    validate::network::NetworkValidator,
+    api::{
+        amount::{amount_from_cis2_units, amount_from_token_units, amount_from_uccd},
+        cis2::{Cis2Tokens, cis2_token_from_currency},
+        error::{ApiError, ApiResult},
+        query::{Address, account_address_from_string},
+    },
+    validate::{account::is_ccd_symbol, network::NetworkValidator},
+};
+use concordium_rust_sdk::{
+    cis2,
+    protocol_level_tokens::{AccountToken, TokenId},
+    types::{Address as ChainAddress, ContractAddress, hashes::BlockHash},
 };
 use rosetta::models::*;
 use std::ops::Deref;
@@ -11,6 +21,7 @@ pub struct AccountApi {
     account_validator: AccountValidator,
     network_validator: NetworkValidator,
     query_helper: QueryHelper,
+    cis2_tokens: Cis2Tokens,
 }
 
 impl AccountApi {
@@ -18,11 +29,13 @@ impl AccountApi {
         account_validator: AccountValidator,
         network_validator: NetworkValidator,
         query_helper: QueryHelper,
+        cis2_tokens: Cis2Tokens,
     ) -> Self {
         Self {
             account_validator,
             network_validator,
             query_helper,
+            cis2_tokens,
         }
     }
 
@@ -32,17 +45,131 @@ impl AccountApi {
     ) -> ApiResult<AccountBalanceResponse> {
         self.network_validator
             .validate_network_identifier(*req.network_identifier)?;
-        self.account_validator.validate_currencies(req.currencies)?;
-        let (block_info, amount) = self
+        self.account_validator
+            .validate_currencies(req.currencies.clone())?;
+        let (block_info, amount, tokens) = self
             .query_helper
             .query_account_balance(req.block_identifier, req.account_identifier.deref())
             .await?;
+        let ccd_balance = amount_from_uccd(amount.micro_ccd() as i128);
+        let balances = match req.currencies {
+            // Return the balances of CCD and all tokens held by the account.
+            None => {
+                let mut res = vec![ccd_balance];
+                res.extend(tokens.iter().map(token_balance));
+                res
+            }
+            Some(currencies) => {
+                let mut res = vec![];
+                for c in currencies {
+                    if is_ccd_symbol(&c) {
+                        res.push(ccd_balance.clone());
+                    } else if req.account_identifier.sub_account.is_some() {
+                        // Sub-accounts only partition the CCD balance.
+                        return Err(ApiError::InvalidCurrency);
+                    } else if let Some((contract, token_id)) = cis2_token_from_currency(&c)? {
+                        res.push(
+                            self.cis2_balance(
+                                &c,
+                                contract,
+                                token_id,
+                                &req.account_identifier.address,
+                                block_info.block_hash,
+                            )
+                            .await?,
+                        );
+                    } else {
+                        res.push(
+                            self.requested_token_balance(&c, &tokens, block_info.block_hash)
+                                .await?,
+                        );
+                    }
+                }
+                res
+            }
+        };
         Ok(AccountBalanceResponse::new(
             BlockIdentifier::new(
                 block_info.block_height.height as i64,
                 block_info.block_hash.to_string(),
             ),
-            vec![amount_from_uccd(amount.micro_ccd() as i128)],
+            balances,
         ))
     }
+
+    /// Balance of the token with the requested currency (whose symbol is
+    /// the token ID). If the account doesn't hold the token, it's resolved as
+    /// of the given block to validate that it exists, and the balance is 0.
+    async fn requested_token_balance(
+        &self,
+        currency: &Currency,
+        tokens: &[AccountToken],
+        block_hash: BlockHash,
+    ) -> ApiResult<Amount> {
+        // Token IDs are case insensitive.
+        let balance = match tokens
+            .iter()
+            .find(|t| t.token_id.as_ref().eq_ignore_ascii_case(&currency.symbol))
+        {
+            Some(t) => token_balance(t),
+            None => {
+                let token_id = TokenId::try_from(currency.symbol.clone())
+                    .map_err(|_| ApiError::InvalidCurrency)?;
+                let token_info = self
+                    .query_helper
+                    .query_token_info(token_id, block_hash)
+                    .await?;
+                amount_from_token_units(
+                    0,
+                    token_info.token_id.as_ref(),
+                    token_info.token_state.decimals,
+                )
+            }
+        };
+        if balance.currency.decimals != currency.decimals {
+            return Err(ApiError::InvalidCurrency);
+        }
+        Ok(balance)
+    }
+
+    /// Balance of the CIS-2 token identified by the requested currency.
+    /// The decimals of the token aren't available on chain, so the token must
+    /// be tracked and the balance is given in the currency of the tracked token
+    /// (like in the operations reported by '/block').
+    async fn cis2_balance(
+        &self,
+        currency: &Currency,
+        contract: ContractAddress,
+        token_id: cis2::TokenId,
+        address: &str,
+        block_hash: BlockHash,
+    ) -> ApiResult<Amount> {
+        let no_tokens_matched = || ApiError::NoTokensMatched(currency.symbol.clone());
+        let token = self
+            .cis2_tokens
+            .get(&contract, &token_id)
+            .ok_or_else(no_tokens_matched)?;
+        if currency.decimals != token.decimals as i32 {
+            return Err(no_tokens_matched());
+        }
+        let owner = match account_address_from_string(address)? {
+            Address::Account(a) => ChainAddress::Account(a),
+            Address::Contract(c) => ChainAddress::Contract(c),
+            // Virtual accounts cannot hold CIS-2 tokens.
+            _ => return Err(ApiError::InvalidCurrency),
+        };
+        let amount = self
+            .query_helper
+            .query_cis2_balance(contract, token_id, owner, &block_hash, &currency.symbol)
+            .await?;
+        Ok(amount_from_cis2_units(amount.0.to_string(), token))
+    }
+}
+
+fn token_balance(token: &AccountToken) -> Amount {
+    amount_from_token_units(
+        token.state.balance.value() as i128,
+        token.token_id.as_ref(),
+        token.state.balance.decimals(),
+    )
 }
@@ -1,12 +1,52 @@
 use crate::{
-    api::error::{ApiError, ApiResult},
+    api::{
+        cis2::{Cis2CurrencyMetadata, Cis2Token},
+        error::{ApiError, ApiResult},
+        transaction::contract_address_string,
+    },
     validate::account::validate_currency,
 };
 use rosetta::models::{Amount, Currency};
 use std::ops::Deref;
 
+pub fn ccd_currency() -> Currency {
+    Currency::new("CCD".to_string(), 6)
+}
+
 pub fn amount_from_uccd(v: i128) -> Amount {
-    Amount::new(v.to_string(), Currency::new("CCD".to_string(), 6))
+    Amount::new(v.to_string(), ccd_currency())
+}
+
+/// Currency of a protocol-level token (PLT): The symbol is the token ID.
+pub fn token_currency(token_id: &str, decimals: u8) -> Currency {
+    Currency::new(token_id.to_string(), decimals as i32)
+}
+
+/// Amount of a protocol-level token (PLT) in the smallest unit of the token.
+pub fn amount_from_token_units(v: i128, token_id: &str, decimals: u8) -> Amount {
+    Amount::new(v.to_string(), token_currency(token_id, decimals))
+}
+
+/// Currency of a CIS-2 token: The symbol is the contract address followed by
+/// the token ID and the metadata contains both separately.
+pub fn cis2_currency(token: &Cis2Token) -> Currency {
+    Currency {
+        symbol: token.symbol(),
+        decimals: token.decimals as i32,
+        metadata: Some(
+            serde_json::to_value(Cis2CurrencyMetadata {
+                contract_address: contract_address_string(&token.contract),
+                token_id: token.token_id.to_string(),
+            })
+            .unwrap(),
+        ),
+    }
+}
+
+/// Amount of a CIS-2 token in the smallest unit of the token. The value is
+/// given as a string as CIS-2 token amounts are unbounded.
+pub fn amount_from_cis2_units(value: String, token: &Cis2Token) -> Amount {
+    Amount::new(value, cis2_currency(token))
 }
 
 pub fn uccd_from_amount(v: &Amount) -> ApiResult<i128> {
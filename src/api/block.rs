@@ -2,6 +2,7 @@ use crate::{
     NetworkValidator,
     api::{
         amount::amount_from_uccd,
+        cis2::Cis2Tokens,
         error::{ApiError, ApiResult},
         query::QueryHelper,
         transaction::*,
@@ -9,23 +10,30 @@ use crate::{
 };
 use concordium_rust_sdk::{
     common::SerdeSerialize,
-    types::{BakerId, SpecialTransactionOutcome},
+    types::{BakerId, SpecialTransactionOutcome, hashes::TransactionHash},
     v2::{IntoBlockIdentifier, Upward},
 };
 use futures::{TryStreamExt, stream::StreamExt};
 use log::warn;
 use rosetta::models::*;
-use std::cmp::max;
+use std::{cmp::max, str::FromStr};
 
 #[derive(Clone)]
 pub struct BlockApi {
     network_validator: NetworkValidator,
     query_helper: QueryHelper,
+    cis2_tokens: Cis2Tokens,
+    /// Number of transactions above which the transactions of a block are
+    /// only returned as identifiers in 'other_transactions'.
+    max_block_transactions: Option<u64>,
 }
 
 #[derive(SerdeSerialize)]
 struct BlockMetadata {
     baker_id: Option<BakerId>,
+    /// Whether the block is finalized. Blocks that aren't finalized may still
+    /// be removed from the chain.
+    finalized: bool,
 }
 
 /// Metadata for a validator suspended or validator primed for suspension event.
@@ -35,10 +43,17 @@ pub struct ValidatorEventMetadata {
 }
 
 impl BlockApi {
-    pub fn new(network_validator: NetworkValidator, query_helper: QueryHelper) -> Self {
+    pub fn new(
+        network_validator: NetworkValidator,
+        query_helper: QueryHelper,
+        cis2_tokens: Cis2Tokens,
+        max_block_transactions: Option<u64>,
+    ) -> Self {
         Self {
             network_validator,
             query_helper,
+            cis2_tokens,
+            max_block_transactions,
         }
     }
 
@@ -49,8 +64,23 @@ impl BlockApi {
             .await?;
         self.network_validator
             .validate_network_identifier(*req.network_identifier)?;
+        if let Some(res) = self.query_helper.cache.block(&block_info.block_hash) {
+            return Ok(res.as_ref().clone());
+        }
 
-        Ok(BlockResponse {
+        // The transactions of large blocks are only returned as identifiers (except for the
+        // synthetic tokenomics transaction) to be fetched individually using '/block/transaction'.
+        let (transactions, other_transactions) = match self.max_block_transactions {
+            Some(max) if block_info.transaction_count > max => (
+                vec![self.tokenomics_transaction(block_info.block_hash).await?],
+                Some(
+                    self.block_transaction_identifiers(block_info.block_hash)
+                        .await?,
+                ),
+            ),
+            _ => (self.block_transactions(block_info.block_hash).await?, None),
+        };
+        let res = BlockResponse {
             block: Some(Box::new(Block {
                 block_identifier: Box::new(BlockIdentifier::new(
                     block_info.block_height.height as i64,
@@ -61,59 +91,119 @@ impl BlockApi {
                     block_info.block_parent.to_string(),
                 )),
                 timestamp: block_info.block_slot_time.timestamp_millis(),
-                transactions: self.block_transactions(block_info.block_hash).await?,
+                transactions,
                 metadata: Some(
                     serde_json::to_value(BlockMetadata {
                         baker_id: block_info.block_baker,
+                        finalized: block_info.finalized,
                     })
                     .unwrap(),
                 ),
             })),
-            other_transactions: None, // currently just expanding all transactions inline
-        })
+            other_transactions,
+        };
+        // Finalized blocks never change, so their responses may be served from the cache.
+        if block_info.finalized {
+            self.query_helper
+                .cache
+                .insert_block(block_info.block_hash, res.clone());
+        }
+        Ok(res)
     }
 
     pub async fn block_transaction(
         &self,
         req: BlockTransactionRequest,
     ) -> ApiResult<BlockTransactionResponse> {
-        let tx_status = self
+        self.network_validator
+            .validate_network_identifier(*req.network_identifier)?;
+        // Both fields of the block identifier are required and must refer to the same block.
+        let block_info = self
             .query_helper
-            .query_transaction_status(req.transaction_identifier.hash)
+            .query_block_info(Some(Box::new(PartialBlockIdentifier {
+                index: Some(req.block_identifier.index),
+                hash: Some(req.block_identifier.hash),
+            })))
             .await?;
-        if let Some((bh, tx)) = tx_status.is_finalized() {
-            if req.block_identifier.hash == bh.to_string() {
-                Ok(BlockTransactionResponse::new(map_transaction(
-                    tx.to_owned(),
-                )))
-            } else {
-                Err(ApiError::InvalidBlockTransactionRequest)
+        let hash_string = req.transaction_identifier.hash;
+        // The transactions of cached blocks are looked up in the cached response.
+        if let Some(transaction) = self
+            .query_helper
+            .cache
+            .block(&block_info.block_hash)
+            .and_then(|res| {
+                res.block.as_ref().and_then(|b| {
+                    b.transactions
+                        .iter()
+                        .find(|tx| tx.transaction_identifier.hash == hash_string)
+                        .cloned()
+                })
+            })
+        {
+            return Ok(BlockTransactionResponse::new(transaction));
+        }
+        if hash_string == TRANSACTION_HASH_TOKENOMICS {
+            return Ok(BlockTransactionResponse::new(
+                self.tokenomics_transaction(block_info.block_hash).await?,
+            ));
+        }
+        let hash = TransactionHash::from_str(hash_string.as_str())
+            .map_err(|e| ApiError::InvalidTransactionIdentifier(hash_string.clone(), e))?;
+        let mut summaries = self
+            .query_helper
+            .query_block_item_summary(block_info.block_hash)
+            .await?;
+        while let Some(summary) = summaries.try_next().await? {
+            if summary.hash == hash {
+                return Ok(BlockTransactionResponse::new(map_transaction(
+                    summary,
+                    &self.cis2_tokens,
+                )));
             }
-        } else {
-            Err(ApiError::NoTransactionsMatched)
         }
+        Err(ApiError::NoTransactionsMatched)
     }
 
-    async fn block_transactions(
+    pub async fn block_transactions(
         &self,
         block_id: impl IntoBlockIdentifier + Clone,
     ) -> ApiResult<Vec<Transaction>> {
-        // Synthetic transaction that contains all the minting and rewards operations.
-        // Inspired by the "coinbase" transaction in Bitcoin.
-        let tokenomics_transaction = Transaction::new(
-            TransactionIdentifier::new(TRANSACTION_HASH_TOKENOMICS.to_string()),
-            self.tokenomics_transaction_operations(block_id.clone())
-                .await?,
-        );
+        let tokenomics_transaction = self.tokenomics_transaction(block_id.clone()).await?;
         let summaries = self.query_helper.query_block_item_summary(block_id).await?;
-        let transactions: Vec<Transaction> =
-            summaries.map_ok(map_transaction).try_collect().await?;
+        let transactions: Vec<Transaction> = summaries
+            .map_ok(|s| map_transaction(s, &self.cis2_tokens))
+            .try_collect()
+            .await?;
 
         let mut res = vec![tokenomics_transaction];
         res.extend(transactions);
         Ok(res)
     }
 
+    /// Identifiers of the (non-synthetic) transactions of the block.
+    async fn block_transaction_identifiers(
+        &self,
+        block_id: impl IntoBlockIdentifier,
+    ) -> ApiResult<Vec<TransactionIdentifier>> {
+        let summaries = self.query_helper.query_block_item_summary(block_id).await?;
+        summaries
+            .map_ok(|s| TransactionIdentifier::new(s.hash.to_string()))
+            .try_collect()
+            .await
+    }
+
+    /// Synthetic transaction that contains all the minting and rewards operations.
+    /// Inspired by the "coinbase" transaction in Bitcoin.
+    async fn tokenomics_transaction(
+        &self,
+        block_id: impl IntoBlockIdentifier,
+    ) -> ApiResult<Transaction> {
+        Ok(Transaction::new(
+            TransactionIdentifier::new(TRANSACTION_HASH_TOKENOMICS.to_string()),
+            self.tokenomics_transaction_operations(block_id).await?,
+        ))
+    }
+
     async fn tokenomics_transaction_operations(
         &self,
         block_id: impl IntoBlockIdentifier,
@@ -126,6 +216,11 @@ impl BlockApi {
         };
         let mut res = vec![];
         let mut current_pool_owner = None;
+        // Identifiers of the operations minting into the reward accounts in this block
+        // for linking the operations that distribute from these accounts. As the
+        // mint is recorded before it's distributed, it always has a lower index.
+        let mut mint_baking_reward_id = None;
+        let mut mint_finalization_reward_id = None;
 
         let mut special_events = self
             .query_helper
@@ -147,65 +242,72 @@ impl BlockApi {
                     mint_finalization_reward,
                     mint_platform_development_charge,
                     foundation_account,
-                } => res.extend(vec![
-                    Operation {
-                        operation_identifier: Box::new(OperationIdentifier::new(next_index(
-                            &mut index_offset,
-                        ))),
-                        related_operations: None,
-                        _type: OPERATION_TYPE_MINT_BAKING_REWARD.to_string(),
-                        status: Some(OPERATION_STATUS_OK.to_string()),
-                        account: Some(Box::new(AccountIdentifier::new(
-                            ACCOUNT_REWARD_BAKING.to_string(),
-                        ))),
-                        amount: Some(Box::new(amount_from_uccd(
-                            mint_baking_reward.micro_ccd() as i128
-                        ))),
-                        coin_change: None,
-                        metadata: None,
-                    },
-                    Operation {
-                        operation_identifier: Box::new(OperationIdentifier::new(next_index(
-                            &mut index_offset,
-                        ))),
-                        related_operations: None,
-                        _type: OPERATION_TYPE_MINT_FINALIZATION_REWARD.to_string(),
-                        status: Some(OPERATION_STATUS_OK.to_string()),
-                        account: Some(Box::new(AccountIdentifier::new(
-                            ACCOUNT_REWARD_FINALIZATION.to_string(),
-                        ))),
-                        amount: Some(Box::new(amount_from_uccd(
-                            mint_finalization_reward.micro_ccd() as i128,
-                        ))),
-                        coin_change: None,
-                        metadata: None,
-                    },
-                    Operation {
-                        operation_identifier: Box::new(OperationIdentifier::new(next_index(
-                            &mut index_offset,
-                        ))),
-                        related_operations: None,
-                        _type: OPERATION_TYPE_MINT_PLATFORM_DEVELOPMENT_CHARGE.to_string(),
-                        status: Some(OPERATION_STATUS_OK.to_string()),
-                        account: Some(Box::new(AccountIdentifier::new(
-                            foundation_account.to_string(),
-                        ))),
-                        amount: Some(Box::new(amount_from_uccd(
-                            mint_platform_development_charge.micro_ccd() as i128,
-                        ))),
-                        coin_change: None,
-                        metadata: None,
-                    },
-                ]),
+                } => {
+                    let baking_reward_id = OperationIdentifier::new(next_index(&mut index_offset));
+                    let finalization_reward_id =
+                        OperationIdentifier::new(next_index(&mut index_offset));
+                    mint_baking_reward_id = Some(baking_reward_id.clone());
+                    mint_finalization_reward_id = Some(finalization_reward_id.clone());
+                    res.extend(vec![
+                        Operation {
+                            operation_identifier: Box::new(baking_reward_id),
+                            related_operations: None,
+                            _type: OPERATION_TYPE_MINT_BAKING_REWARD.to_string(),
+                            status: Some(OPERATION_STATUS_OK.to_string()),
+                            account: Some(Box::new(AccountIdentifier::new(
+                                ACCOUNT_REWARD_BAKING.to_string(),
+                            ))),
+                            amount: Some(Box::new(amount_from_uccd(
+                                mint_baking_reward.micro_ccd() as i128,
+                            ))),
+                            coin_change: None,
+                            metadata: None,
+                        },
+                        Operation {
+                            operation_identifier: Box::new(finalization_reward_id),
+                            related_operations: None,
+                            _type: OPERATION_TYPE_MINT_FINALIZATION_REWARD.to_string(),
+                            status: Some(OPERATION_STATUS_OK.to_string()),
+                            account: Some(Box::new(AccountIdentifier::new(
+                                ACCOUNT_REWARD_FINALIZATION.to_string(),
+                            ))),
+                            amount: Some(Box::new(amount_from_uccd(
+                                mint_finalization_reward.micro_ccd() as i128,
+                            ))),
+                            coin_change: None,
+                            metadata: None,
+                        },
+                        Operation {
+                            operation_identifier: Box::new(OperationIdentifier::new(next_index(
+                                &mut index_offset,
+                            ))),
+                            related_operations: None,
+                            _type: OPERATION_TYPE_MINT_PLATFORM_DEVELOPMENT_CHARGE.to_string(),
+                            status: Some(OPERATION_STATUS_OK.to_string()),
+                            account: Some(Box::new(AccountIdentifier::new(
+                                foundation_account.to_string(),
+                            ))),
+                            amount: Some(Box::new(amount_from_uccd(
+                                mint_platform_development_charge.micro_ccd() as i128,
+                            ))),
+                            coin_change: None,
+                            metadata: None,
+                        },
+                    ]);
+                }
                 SpecialTransactionOutcome::BlockReward {
+                    transaction_fees,
                     baker_reward,
                     baker,
                     foundation_charge,
                     foundation_account,
                     ..
                 } => {
-                    // TODO Add gas account operations.
-                    if baker_reward.micro_ccd() != 0 {
+                    // The transaction fees of the block are collected in the GAS account
+                    // (balancing the fee operations of the block's transactions),
+                    // which then pays out the rewards. The net change of the GAS account
+                    // is the difference between its new and old balance.
+                    if transaction_fees.micro_ccd() != 0 {
                         res.push(Operation {
                             operation_identifier: Box::new(OperationIdentifier::new(next_index(
                                 &mut index_offset,
@@ -213,6 +315,27 @@ impl BlockApi {
                             related_operations: None,
                             _type: OPERATION_TYPE_BLOCK_REWARD.to_string(),
                             status: Some(OPERATION_STATUS_OK.to_string()),
+                            account: Some(Box::new(AccountIdentifier::new(
+                                ACCOUNT_GAS.to_string(),
+                            ))),
+                            amount: Some(Box::new(amount_from_uccd(
+                                transaction_fees.micro_ccd() as i128
+                            ))),
+                            coin_change: None,
+                            metadata: None,
+                        });
+                    }
+                    let mut payout_sum: i128 = 0;
+                    let mut operation_identifiers = vec![];
+                    if baker_reward.micro_ccd() != 0 {
+                        payout_sum += baker_reward.micro_ccd() as i128;
+                        let id = OperationIdentifier::new(next_index(&mut index_offset));
+                        operation_identifiers.push(id.clone());
+                        res.push(Operation {
+                            operation_identifier: Box::new(id),
+                            related_operations: None,
+                            _type: OPERATION_TYPE_BLOCK_REWARD.to_string(),
+                            status: Some(OPERATION_STATUS_OK.to_string()),
                             account: Some(Box::new(AccountIdentifier::new(baker.to_string()))),
                             amount: Some(Box::new(amount_from_uccd(
                                 baker_reward.micro_ccd() as i128
@@ -222,10 +345,11 @@ impl BlockApi {
                         });
                     }
                     if foundation_charge.micro_ccd() != 0 {
+                        payout_sum += foundation_charge.micro_ccd() as i128;
+                        let id = OperationIdentifier::new(next_index(&mut index_offset));
+                        operation_identifiers.push(id.clone());
                         res.push(Operation {
-                            operation_identifier: Box::new(OperationIdentifier::new(next_index(
-                                &mut index_offset,
-                            ))),
+                            operation_identifier: Box::new(id),
                             related_operations: None,
                             _type: OPERATION_TYPE_BLOCK_REWARD.to_string(),
                             status: Some(OPERATION_STATUS_OK.to_string()),
@@ -237,7 +361,23 @@ impl BlockApi {
                             ))),
                             coin_change: None,
                             metadata: None,
-                        })
+                        });
+                    }
+                    if payout_sum != 0 {
+                        res.push(Operation {
+                            operation_identifier: Box::new(OperationIdentifier::new(next_index(
+                                &mut index_offset,
+                            ))),
+                            related_operations: Some(operation_identifiers),
+                            _type: OPERATION_TYPE_BLOCK_REWARD.to_string(),
+                            status: Some(OPERATION_STATUS_OK.to_string()),
+                            account: Some(Box::new(AccountIdentifier::new(
+                                ACCOUNT_GAS.to_string(),
+                            ))),
+                            amount: Some(Box::new(amount_from_uccd(-payout_sum))),
+                            coin_change: None,
+                            metadata: None,
+                        });
                     }
                 }
                 SpecialTransactionOutcome::BakingRewards { baker_rewards, .. } => {
@@ -260,6 +400,7 @@ impl BlockApi {
                             metadata: None,
                         })
                     }
+                    operation_identifiers.extend(mint_baking_reward_id.clone());
                     res.push(Operation {
                         operation_identifier: Box::new(OperationIdentifier::new(next_index(
                             &mut index_offset,
@@ -301,6 +442,7 @@ impl BlockApi {
                             metadata: None,
                         })
                     }
+                    operation_identifiers.extend(mint_finalization_reward_id.clone());
                     res.push(Operation {
                         operation_identifier: Box::new(OperationIdentifier::new(next_index(
                             &mut index_offset,
@@ -336,10 +478,9 @@ impl BlockApi {
                                 Some(id) => id.to_string(),
                             }
                         );
+                        let reward_id = OperationIdentifier::new(next_index(&mut index_offset));
                         res.push(Operation {
-                            operation_identifier: Box::new(OperationIdentifier::new(next_index(
-                                &mut index_offset,
-                            ))),
+                            operation_identifier: Box::new(reward_id.clone()),
                             related_operations: None,
                             _type: OPERATION_TYPE_PAYDAY_TRANSACTION_FEES_REWARD.to_string(),
                             status: Some(OPERATION_STATUS_OK.to_string()),
@@ -354,7 +495,7 @@ impl BlockApi {
                             operation_identifier: Box::new(OperationIdentifier::new(next_index(
                                 &mut index_offset,
                             ))),
-                            related_operations: None,
+                            related_operations: Some(vec![reward_id]),
                             _type: OPERATION_TYPE_PAYDAY_TRANSACTION_FEES_REWARD.to_string(),
                             status: Some(OPERATION_STATUS_OK.to_string()),
                             account: Some(Box::new(AccountIdentifier::new(pool_account_address))),
@@ -366,10 +507,9 @@ impl BlockApi {
                         });
                     }
                     if baker_reward.micro_ccd() != 0 {
+                        let reward_id = OperationIdentifier::new(next_index(&mut index_offset));
                         res.push(Operation {
-                            operation_identifier: Box::new(OperationIdentifier::new(next_index(
-                                &mut index_offset,
-                            ))),
+                            operation_identifier: Box::new(reward_id.clone()),
                             related_operations: None,
                             _type: OPERATION_TYPE_PAYDAY_BAKING_REWARD.to_string(),
                             status: Some(OPERATION_STATUS_OK.to_string()),
@@ -384,7 +524,12 @@ impl BlockApi {
                             operation_identifier: Box::new(OperationIdentifier::new(next_index(
                                 &mut index_offset,
                             ))),
-                            related_operations: None,
+                            related_operations: Some(
+                                [Some(reward_id), mint_baking_reward_id.clone()]
+                                    .into_iter()
+                                    .flatten()
+                                    .collect(),
+                            ),
                             _type: OPERATION_TYPE_PAYDAY_BAKING_REWARD.to_string(),
                             status: Some(OPERATION_STATUS_OK.to_string()),
                             account: Some(Box::new(AccountIdentifier::new(
@@ -398,10 +543,9 @@ impl BlockApi {
                         });
                     }
                     if finalization_reward.micro_ccd() != 0 {
+                        let reward_id = OperationIdentifier::new(next_index(&mut index_offset));
                         res.push(Operation {
-                            operation_identifier: Box::new(OperationIdentifier::new(next_index(
-                                &mut index_offset,
-                            ))),
+                            operation_identifier: Box::new(reward_id.clone()),
                             related_operations: None,
                             _type: OPERATION_TYPE_PAYDAY_FINALIZATION_REWARD.to_string(),
                             status: Some(OPERATION_STATUS_OK.to_string()),
@@ -416,7 +560,12 @@ impl BlockApi {
                             operation_identifier: Box::new(OperationIdentifier::new(next_index(
                                 &mut index_offset,
                             ))),
-                            related_operations: None,
+                            related_operations: Some(
+                                [Some(reward_id), mint_finalization_reward_id.clone()]
+                                    .into_iter()
+                                    .flatten()
+                                    .collect(),
+                            ),
                             _type: OPERATION_TYPE_PAYDAY_FINALIZATION_REWARD.to_string(),
                             status: Some(OPERATION_STATUS_OK.to_string()),
                             account: Some(Box::new(AccountIdentifier::new(
@@ -434,10 +583,9 @@ impl BlockApi {
                     foundation_account,
                     development_charge,
                 } => {
+                    let reward_id = OperationIdentifier::new(next_index(&mut index_offset));
                     res.push(Operation {
-                        operation_identifier: Box::new(OperationIdentifier::new(next_index(
-                            &mut index_offset,
-                        ))),
+                        operation_identifier: Box::new(reward_id.clone()),
                         related_operations: None,
                         _type: OPERATION_TYPE_PAYDAY_FOUNDATION_REWARD.to_string(),
                         status: Some(OPERATION_STATUS_OK.to_string()),
@@ -454,7 +602,7 @@ impl BlockApi {
                         operation_identifier: Box::new(OperationIdentifier::new(next_index(
                             &mut index_offset,
                         ))),
-                        related_operations: None,
+                        related_operations: Some(vec![reward_id]),
                         _type: OPERATION_TYPE_PAYDAY_FOUNDATION_REWARD.to_string(),
                         status: Some(OPERATION_STATUS_OK.to_string()),
                         account: Some(Box::new(AccountIdentifier::new(
@@ -468,14 +616,16 @@ impl BlockApi {
                     });
                 }
                 SpecialTransactionOutcome::BlockAccrueReward {
+                    transaction_fees,
                     baker_reward,
                     passive_reward,
                     foundation_charge,
                     baker_id,
                     ..
                 } => {
-                    // TODO Add gas account operations.
-                    if foundation_charge.micro_ccd() != 0 {
+                    // Same as for 'BlockReward' except that the rewards are paid out
+                    // to the accrue accounts.
+                    if transaction_fees.micro_ccd() != 0 {
                         res.push(Operation {
                             operation_identifier: Box::new(OperationIdentifier::new(next_index(
                                 &mut index_offset,
@@ -483,6 +633,27 @@ impl BlockApi {
                             related_operations: None,
                             _type: OPERATION_TYPE_BLOCK_ACCRUE_REWARD.to_string(),
                             status: Some(OPERATION_STATUS_OK.to_string()),
+                            account: Some(Box::new(AccountIdentifier::new(
+                                ACCOUNT_GAS.to_string(),
+                            ))),
+                            amount: Some(Box::new(amount_from_uccd(
+                                transaction_fees.micro_ccd() as i128
+                            ))),
+                            coin_change: None,
+                            metadata: None,
+                        });
+                    }
+                    let mut payout_sum: i128 = 0;
+                    let mut operation_identifiers = vec![];
+                    if foundation_charge.micro_ccd() != 0 {
+                        payout_sum += foundation_charge.micro_ccd() as i128;
+                        let id = OperationIdentifier::new(next_index(&mut index_offset));
+                        operation_identifiers.push(id.clone());
+                        res.push(Operation {
+                            operation_identifier: Box::new(id),
+                            related_operations: None,
+                            _type: OPERATION_TYPE_BLOCK_ACCRUE_REWARD.to_string(),
+                            status: Some(OPERATION_STATUS_OK.to_string()),
                             account: Some(Box::new(AccountIdentifier::new(
                                 ACCOUNT_ACCRUE_FOUNDATION.to_string(),
                             ))),
@@ -494,10 +665,11 @@ impl BlockApi {
                         });
                     }
                     if passive_reward.micro_ccd() != 0 {
+                        payout_sum += passive_reward.micro_ccd() as i128;
+                        let id = OperationIdentifier::new(next_index(&mut index_offset));
+                        operation_identifiers.push(id.clone());
                         res.push(Operation {
-                            operation_identifier: Box::new(OperationIdentifier::new(next_index(
-                                &mut index_offset,
-                            ))),
+                            operation_identifier: Box::new(id),
                             related_operations: None,
                             _type: OPERATION_TYPE_BLOCK_ACCRUE_REWARD.to_string(),
                             status: Some(OPERATION_STATUS_OK.to_string()),
@@ -513,10 +685,11 @@ impl BlockApi {
                         });
                     }
                     if baker_reward.micro_ccd() != 0 {
+                        payout_sum += baker_reward.micro_ccd() as i128;
+                        let id = OperationIdentifier::new(next_index(&mut index_offset));
+                        operation_identifiers.push(id.clone());
                         res.push(Operation {
-                            operation_identifier: Box::new(OperationIdentifier::new(next_index(
-                                &mut index_offset,
-                            ))),
+                            operation_identifier: Box::new(id),
                             related_operations: None,
                             _type: OPERATION_TYPE_BLOCK_ACCRUE_REWARD.to_string(),
                             status: Some(OPERATION_STATUS_OK.to_string()),
@@ -531,6 +704,22 @@ impl BlockApi {
                             metadata: None,
                         });
                     }
+                    if payout_sum != 0 {
+                        res.push(Operation {
+                            operation_identifier: Box::new(OperationIdentifier::new(next_index(
+                                &mut index_offset,
+                            ))),
+                            related_operations: Some(operation_identifiers),
+                            _type: OPERATION_TYPE_BLOCK_ACCRUE_REWARD.to_string(),
+                            status: Some(OPERATION_STATUS_OK.to_string()),
+                            account: Some(Box::new(AccountIdentifier::new(
+                                ACCOUNT_GAS.to_string(),
+                            ))),
+                            amount: Some(Box::new(amount_from_uccd(-payout_sum))),
+                            coin_change: None,
+                            metadata: None,
+                        });
+                    }
                 }
                 SpecialTransactionOutcome::ValidatorPrimedForSuspension { baker_id, account } => {
                     res.push(Operation {
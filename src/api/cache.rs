@@ -0,0 +1,257 @@
+use concordium_rust_sdk::types::{hashes::BlockHash, queries::BlockInfo};
+use log::info;
+use rosetta::models::BlockResponse;
+use std::{
+    collections::BTreeMap,
+    mem::size_of,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+/// Interval between reports of the cache statistics.
+const STATISTICS_INTERVAL: Duration = Duration::from_secs(300);
+/// Rough estimates of the memory usage of the parts of a '/block' response,
+/// which are used to estimate the size of the response without traversing it
+/// in full.
+const BLOCK_SIZE_ESTIMATE: usize = 1024;
+const TRANSACTION_SIZE_ESTIMATE: usize = 512;
+const OPERATION_SIZE_ESTIMATE: usize = 1024;
+const TRANSACTION_IDENTIFIER_SIZE_ESTIMATE: usize = 128;
+
+/// Bounded in-memory cache of data derived from finalized blocks, which never
+/// changes. Entries are evicted in least recently used order once either the
+/// number of entries or their estimated total size exceeds the configured
+/// limit.
+#[derive(Clone)]
+pub struct BlockCache {
+    max_entries: usize,
+    max_bytes: usize,
+    state: Arc<Mutex<CacheState>>,
+}
+
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum CacheKey {
+    /// Response of '/block' for the block with the given hash.
+    Block(BlockHash),
+    /// Info of the block with the given hash.
+    BlockInfo(BlockHash),
+    /// Hash of the finalized block at the given height.
+    BlockHash(u64),
+}
+
+impl CacheKey {
+    fn kind(&self) -> usize {
+        match self {
+            CacheKey::Block(_) => 0,
+            CacheKey::BlockInfo(_) => 1,
+            CacheKey::BlockHash(_) => 2,
+        }
+    }
+}
+
+#[derive(Clone)]
+enum CacheValue {
+    Block(Arc<BlockResponse>),
+    BlockInfo(Arc<BlockInfo>),
+    BlockHash(BlockHash),
+}
+
+struct CacheEntry {
+    value: CacheValue,
+    /// Estimated memory usage of the entry.
+    size_bytes: usize,
+    /// Tick of the most recent use of the entry.
+    last_used: u64,
+}
+
+#[derive(Clone, Copy, Default)]
+struct CacheStatistics {
+    hits: u64,
+    misses: u64,
+}
+
+#[derive(Default)]
+struct CacheState {
+    entries: BTreeMap<CacheKey, CacheEntry>,
+    /// Keys of the entries by the tick of their most recent use (least recently
+    /// used first).
+    usage: BTreeMap<u64, CacheKey>,
+    next_tick: u64,
+    size_bytes: usize,
+    /// Statistics by kind of key.
+    statistics: [CacheStatistics; 3],
+}
+
+impl CacheState {
+    fn tick(&mut self) -> u64 {
+        let res = self.next_tick;
+        self.next_tick += 1;
+        res
+    }
+
+    fn get(&mut self, key: &CacheKey) -> Option<CacheValue> {
+        let tick = self.tick();
+        let stats = &mut self.statistics[key.kind()];
+        match self.entries.get_mut(key) {
+            None => {
+                stats.misses += 1;
+                None
+            }
+            Some(entry) => {
+                stats.hits += 1;
+                self.usage.remove(&entry.last_used);
+                self.usage.insert(tick, key.clone());
+                entry.last_used = tick;
+                Some(entry.value.clone())
+            }
+        }
+    }
+
+    fn remove(&mut self, key: &CacheKey) {
+        if let Some(entry) = self.entries.remove(key) {
+            self.usage.remove(&entry.last_used);
+            self.size_bytes -= entry.size_bytes;
+        }
+    }
+
+    fn insert(&mut self, key: CacheKey, value: CacheValue, size_bytes: usize) {
+        self.remove(&key);
+        let tick = self.tick();
+        self.usage.insert(tick, key.clone());
+        self.entries.insert(
+            key,
+            CacheEntry {
+                value,
+                size_bytes,
+                last_used: tick,
+            },
+        );
+        self.size_bytes += size_bytes;
+    }
+
+    fn evict(&mut self, max_entries: usize, max_bytes: usize) {
+        while self.entries.len() > max_entries || self.size_bytes > max_bytes {
+            let Some((_, key)) = self.usage.pop_first() else {
+                break;
+            };
+            self.remove(&key);
+        }
+    }
+}
+
+impl BlockCache {
+    /// Create a cache holding up to 'max_entries' entries of an estimated total
+    /// size of up to 'max_bytes'. The cache is disabled if either is zero.
+    pub fn new(max_entries: usize, max_bytes: usize) -> Self {
+        Self {
+            max_entries,
+            max_bytes,
+            state: Arc::new(Mutex::new(CacheState::default())),
+        }
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.max_entries > 0 && self.max_bytes > 0
+    }
+
+    fn get(&self, key: &CacheKey) -> Option<CacheValue> {
+        if !self.is_enabled() {
+            return None;
+        }
+        self.state.lock().unwrap().get(key)
+    }
+
+    fn insert(&self, key: CacheKey, value: CacheValue, size_bytes: usize) {
+        if !self.is_enabled() || size_bytes > self.max_bytes {
+            return;
+        }
+        let mut state = self.state.lock().unwrap();
+        state.insert(key, value, size_bytes);
+        state.evict(self.max_entries, self.max_bytes);
+    }
+
+    pub fn block(&self, block_hash: &BlockHash) -> Option<Arc<BlockResponse>> {
+        match self.get(&CacheKey::Block(*block_hash)) {
+            Some(CacheValue::Block(res)) => Some(res),
+            _ => None,
+        }
+    }
+
+    /// Cache the response of '/block' for a finalized block.
+    pub fn insert_block(&self, block_hash: BlockHash, res: BlockResponse) {
+        let size_bytes = block_response_size_estimate(&res);
+        self.insert(
+            CacheKey::Block(block_hash),
+            CacheValue::Block(Arc::new(res)),
+            size_bytes,
+        );
+    }
+
+    pub fn block_info(&self, block_hash: &BlockHash) -> Option<BlockInfo> {
+        match self.get(&CacheKey::BlockInfo(*block_hash)) {
+            Some(CacheValue::BlockInfo(info)) => Some(info.as_ref().clone()),
+            _ => None,
+        }
+    }
+
+    /// Cache the info of a finalized block.
+    pub fn insert_block_info(&self, info: BlockInfo) {
+        self.insert(
+            CacheKey::BlockInfo(info.block_hash),
+            CacheValue::BlockInfo(Arc::new(info)),
+            size_of::<BlockInfo>(),
+        );
+    }
+
+    pub fn block_hash(&self, height: u64) -> Option<BlockHash> {
+        match self.get(&CacheKey::BlockHash(height)) {
+            Some(CacheValue::BlockHash(hash)) => Some(hash),
+            _ => None,
+        }
+    }
+
+    /// Cache the hash of the finalized block at the given height.
+    pub fn insert_block_hash(&self, height: u64, block_hash: BlockHash) {
+        self.insert(
+            CacheKey::BlockHash(height),
+            CacheValue::BlockHash(block_hash),
+            size_of::<BlockHash>(),
+        );
+    }
+
+    /// Periodically log the number of hits and misses of the cache.
+    pub async fn report_statistics(self) {
+        if !self.is_enabled() {
+            return;
+        }
+        loop {
+            tokio::time::sleep(STATISTICS_INTERVAL).await;
+            let state = self.state.lock().unwrap();
+            let [blocks, block_infos, block_hashes] = state.statistics;
+            info!(
+                "Cache statistics: {} entries of {} bytes; hits/misses: blocks {}/{}, block infos \
+                 {}/{}, block hashes {}/{}.",
+                state.entries.len(),
+                state.size_bytes,
+                blocks.hits,
+                blocks.misses,
+                block_infos.hits,
+                block_infos.misses,
+                block_hashes.hits,
+                block_hashes.misses,
+            );
+        }
+    }
+}
+
+/// Estimate the memory usage of a '/block' response from the number of
+/// transactions and operations that it contains.
+fn block_response_size_estimate(res: &BlockResponse) -> usize {
+    let transactions = res.block.as_ref().map_or(&[][..], |b| &b.transactions[..]);
+    let operation_count: usize = transactions.iter().map(|t| t.operations.len()).sum();
+    let other_transaction_count = res.other_transactions.as_ref().map_or(0, Vec::len);
+    BLOCK_SIZE_ESTIMATE
+        + transactions.len() * TRANSACTION_SIZE_ESTIMATE
+        + operation_count * OPERATION_SIZE_ESTIMATE
+        + other_transaction_count * TRANSACTION_IDENTIFIER_SIZE_ESTIMATE
+}
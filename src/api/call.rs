@@ -0,0 +1,290 @@
+use crate::{
+    NetworkValidator, QueryHelper,
+    api::{
+        error::{ApiError, ApiResult},
+        query::map_query_result,
+    },
+};
+use concordium_rust_sdk::{
+    common::{SerdeDeserialize, SerdeSerialize, types::Amount},
+    id::types::AccountAddress,
+    smart_contracts::common::{OwnedParameter, OwnedReceiveName},
+    types::{
+        Address as ContractInvoker, BakerId, ContractAddress, Energy, hashes::BlockHash,
+        smart_contracts::ContractContext,
+    },
+    v2,
+};
+use rosetta::models::*;
+use serde_json::Value;
+
+pub const CALL_METHOD_GET_ACCOUNT_INFO: &str = "get_account_info";
+pub const CALL_METHOD_GET_CHAIN_PARAMETERS: &str = "get_chain_parameters";
+pub const CALL_METHOD_GET_INSTANCE_INFO: &str = "get_instance_info";
+pub const CALL_METHOD_GET_NEXT_ACCOUNT_SEQUENCE_NUMBER: &str = "get_next_account_sequence_number";
+pub const CALL_METHOD_GET_PASSIVE_DELEGATION_INFO: &str = "get_passive_delegation_info";
+pub const CALL_METHOD_GET_POOL_INFO: &str = "get_pool_info";
+pub const CALL_METHOD_INVOKE_INSTANCE: &str = "invoke_instance";
+
+/// All methods supported by the Call API.
+pub const CALL_METHODS: [&str; 7] = [
+    CALL_METHOD_GET_ACCOUNT_INFO,
+    CALL_METHOD_GET_CHAIN_PARAMETERS,
+    CALL_METHOD_GET_INSTANCE_INFO,
+    CALL_METHOD_GET_NEXT_ACCOUNT_SEQUENCE_NUMBER,
+    CALL_METHOD_GET_PASSIVE_DELEGATION_INFO,
+    CALL_METHOD_GET_POOL_INFO,
+    CALL_METHOD_INVOKE_INSTANCE,
+];
+
+/// Parameters of methods that only query the state of a block.
+#[derive(SerdeDeserialize)]
+#[serde(deny_unknown_fields)]
+struct BlockParameters {
+    block_identifier: Option<PartialBlockIdentifier>,
+}
+
+#[derive(SerdeDeserialize)]
+#[serde(deny_unknown_fields)]
+struct AccountParameters {
+    block_identifier: Option<PartialBlockIdentifier>,
+    address: AccountAddress,
+}
+
+#[derive(SerdeDeserialize)]
+#[serde(deny_unknown_fields)]
+struct NextAccountSequenceNumberParameters {
+    address: AccountAddress,
+}
+
+#[derive(SerdeDeserialize)]
+#[serde(deny_unknown_fields)]
+struct InstanceParameters {
+    block_identifier: Option<PartialBlockIdentifier>,
+    contract: ContractAddress,
+}
+
+#[derive(SerdeDeserialize)]
+#[serde(deny_unknown_fields)]
+struct PoolParameters {
+    block_identifier: Option<PartialBlockIdentifier>,
+    baker_id: BakerId,
+}
+
+#[derive(SerdeDeserialize)]
+#[serde(deny_unknown_fields)]
+struct InvokeInstanceParameters {
+    block_identifier: Option<PartialBlockIdentifier>,
+    contract: ContractAddress,
+    /// Name of the receive function in the format "<contract>.<function>".
+    method: String,
+    /// Hex encoded parameter.
+    #[serde(default)]
+    parameter: String,
+    invoker: Option<AccountAddress>,
+    /// Amount in uCCD (encoded as a string).
+    amount: Option<Amount>,
+    energy: Option<u64>,
+}
+
+#[derive(SerdeSerialize)]
+struct BlockCallResult<T> {
+    block_identifier: BlockIdentifier,
+    result: T,
+}
+
+#[derive(Clone)]
+pub struct CallApi {
+    network_validator: NetworkValidator,
+    query_helper: QueryHelper,
+}
+
+impl CallApi {
+    pub fn new(network_validator: NetworkValidator, query_helper: QueryHelper) -> Self {
+        Self {
+            network_validator,
+            query_helper,
+        }
+    }
+
+    pub async fn call(&self, req: CallRequest) -> ApiResult<CallResponse> {
+        self.network_validator
+            .validate_network_identifier(*req.network_identifier)?;
+        let method = req.method.as_str();
+        let params = req.parameters;
+        match method {
+            CALL_METHOD_GET_ACCOUNT_INFO => {
+                let p: AccountParameters = parse_parameters(method, params)?;
+                let (block_identifier, block_hash, idempotent) =
+                    self.block(p.block_identifier).await?;
+                let result = map_query_result(
+                    self.query_helper
+                        .client
+                        .clone()
+                        .get_account_info(&v2::AccountIdentifier::Address(p.address), block_hash)
+                        .await
+                        .map(|x| x.response),
+                    ApiError::NoAccountsMatched,
+                )?;
+                block_call_response(block_identifier, result, idempotent)
+            }
+            CALL_METHOD_GET_CHAIN_PARAMETERS => {
+                let p: BlockParameters = parse_parameters(method, params)?;
+                let (block_identifier, block_hash, idempotent) =
+                    self.block(p.block_identifier).await?;
+                let result = map_query_result(
+                    self.query_helper
+                        .client
+                        .clone()
+                        .get_block_chain_parameters(block_hash)
+                        .await
+                        .map(|x| x.response),
+                    ApiError::NoBlocksMatched,
+                )?;
+                block_call_response(block_identifier, result, idempotent)
+            }
+            CALL_METHOD_GET_INSTANCE_INFO => {
+                let p: InstanceParameters = parse_parameters(method, params)?;
+                let (block_identifier, block_hash, idempotent) =
+                    self.block(p.block_identifier).await?;
+                let result = map_query_result(
+                    self.query_helper
+                        .client
+                        .clone()
+                        .get_instance_info(p.contract, block_hash)
+                        .await
+                        .map(|x| x.response),
+                    ApiError::NoContractsMatched,
+                )?;
+                block_call_response(block_identifier, result, idempotent)
+            }
+            CALL_METHOD_GET_NEXT_ACCOUNT_SEQUENCE_NUMBER => {
+                let p: NextAccountSequenceNumberParameters = parse_parameters(method, params)?;
+                let result = map_query_result(
+                    self.query_helper
+                        .client
+                        .clone()
+                        .get_next_account_sequence_number(&p.address)
+                        .await,
+                    ApiError::NoAccountsMatched,
+                )?;
+                // The result depends on the state of the node (including non-finalized blocks
+                // and pending transactions), so the call is never idempotent.
+                call_response(result, false)
+            }
+            CALL_METHOD_GET_PASSIVE_DELEGATION_INFO => {
+                let p: BlockParameters = parse_parameters(method, params)?;
+                let (block_identifier, block_hash, idempotent) =
+                    self.block(p.block_identifier).await?;
+                let result = map_query_result(
+                    self.query_helper
+                        .client
+                        .clone()
+                        .get_passive_delegation_info(block_hash)
+                        .await
+                        .map(|x| x.response),
+                    ApiError::NoBlocksMatched,
+                )?;
+                block_call_response(block_identifier, result, idempotent)
+            }
+            CALL_METHOD_GET_POOL_INFO => {
+                let p: PoolParameters = parse_parameters(method, params)?;
+                let (block_identifier, block_hash, idempotent) =
+                    self.block(p.block_identifier).await?;
+                let result = map_query_result(
+                    self.query_helper
+                        .client
+                        .clone()
+                        .get_pool_info(block_hash, p.baker_id)
+                        .await
+                        .map(|x| x.response),
+                    ApiError::NoPoolsMatched,
+                )?;
+                block_call_response(block_identifier, result, idempotent)
+            }
+            CALL_METHOD_INVOKE_INSTANCE => {
+                let p: InvokeInstanceParameters = parse_parameters(method, params)?;
+                let context = contract_context(method, &p)?;
+                let (block_identifier, block_hash, idempotent) =
+                    self.block(p.block_identifier).await?;
+                let result = map_query_result(
+                    self.query_helper
+                        .client
+                        .clone()
+                        .invoke_instance(block_hash, &context)
+                        .await
+                        .map(|x| x.response),
+                    ApiError::NoContractsMatched,
+                )?;
+                block_call_response(block_identifier, result, idempotent)
+            }
+            _ => Err(ApiError::UnsupportedCallMethod(req.method)),
+        }
+    }
+
+    /// Resolve the block to query. Defaults to the head block.
+    /// Also returns whether a call querying the block is idempotent: This is
+    /// only the case if the block is given explicitly (as the head changes)
+    /// and is finalized (as other blocks may be abandoned).
+    async fn block(
+        &self,
+        block_identifier: Option<PartialBlockIdentifier>,
+    ) -> ApiResult<(BlockIdentifier, BlockHash, bool)> {
+        let explicit = block_identifier.is_some();
+        let block_info = self
+            .query_helper
+            .query_block_info(block_identifier.map(Box::new))
+            .await?;
+        Ok((
+            BlockIdentifier::new(
+                block_info.block_height.height as i64,
+                block_info.block_hash.to_string(),
+            ),
+            block_info.block_hash,
+            explicit && block_info.finalized,
+        ))
+    }
+}
+
+fn parse_parameters<T: serde::de::DeserializeOwned>(method: &str, params: Value) -> ApiResult<T> {
+    serde_json::from_value(params)
+        .map_err(|err| ApiError::InvalidCallParameters(method.to_string(), err.to_string()))
+}
+
+fn contract_context(method: &str, p: &InvokeInstanceParameters) -> ApiResult<ContractContext> {
+    let invalid = |msg: String| ApiError::InvalidCallParameters(method.to_string(), msg);
+    let receive_name = OwnedReceiveName::new(p.method.clone())
+        .map_err(|err| invalid(format!("invalid method '{}': {}", p.method, err)))?;
+    let parameter_bytes = hex::decode(&p.parameter)
+        .map_err(|err| invalid(format!("invalid parameter '{}': {}", p.parameter, err)))?;
+    let parameter = OwnedParameter::try_from(parameter_bytes)
+        .map_err(|err| invalid(format!("invalid parameter: {}", err)))?;
+    Ok(ContractContext {
+        invoker: p.invoker.map(ContractInvoker::Account),
+        contract: p.contract,
+        amount: p.amount.unwrap_or(Amount::from_micro_ccd(0)),
+        method: receive_name,
+        parameter,
+        energy: p.energy.map(Energy::from),
+    })
+}
+
+fn call_response<T: SerdeSerialize>(result: T, idempotent: bool) -> ApiResult<CallResponse> {
+    let result =
+        serde_json::to_value(result).map_err(|err| ApiError::InternalServerError(err.into()))?;
+    Ok(CallResponse::new(result, idempotent))
+}
+
+fn block_call_response<T: SerdeSerialize>(
+    block_identifier: BlockIdentifier,
+    result: T,
+    idempotent: bool,
+) -> ApiResult<CallResponse> {
+    call_response(
+        BlockCallResult {
+            block_identifier,
+            result,
+        },
+        idempotent,
+    )
+}
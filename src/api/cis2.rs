@@ -0,0 +1,248 @@
+use crate::api::{
+    error::{ApiError, ApiResult},
+    query::{Address, account_address_from_string},
+    transaction::{ACCOUNT_CONTRACT_PREFIX, contract_address_string},
+};
+use anyhow::{Context, anyhow};
+use concordium_rust_sdk::{
+    cis2::TokenId,
+    common::{SerdeDeserialize, SerdeSerialize},
+    types::ContractAddress,
+};
+use rosetta::models::Currency;
+use std::{str::FromStr, sync::Arc};
+
+/// Metadata of the currency of a CIS-2 token.
+#[derive(SerdeSerialize, SerdeDeserialize)]
+pub struct Cis2CurrencyMetadata {
+    /// Contract address formatted as in account identifiers.
+    pub contract_address: String,
+    /// Hex encoded token ID.
+    pub token_id: String,
+}
+
+/// Resolve the CIS-2 token of a currency from its metadata.
+/// Returns None if the metadata doesn't name a contract address and token ID.
+pub fn cis2_token_from_currency(c: &Currency) -> ApiResult<Option<(ContractAddress, TokenId)>> {
+    let Some(metadata) = c
+        .metadata
+        .clone()
+        .and_then(|m| serde_json::from_value::<Cis2CurrencyMetadata>(m).ok())
+    else {
+        return Ok(None);
+    };
+    let Address::Contract(contract) = account_address_from_string(&metadata.contract_address)
+        .map_err(|_| ApiError::InvalidCurrency)?
+    else {
+        return Err(ApiError::InvalidCurrency);
+    };
+    let token_id = TokenId::from_str(&metadata.token_id).map_err(|_| ApiError::InvalidCurrency)?;
+    Ok(Some((contract, token_id)))
+}
+
+/// CIS-2 token for which the events logged by its contract are decoded into
+/// operations.
+#[derive(Clone)]
+pub struct Cis2Token {
+    pub contract: ContractAddress,
+    pub token_id: TokenId,
+    /// Number of decimals of the token as given by its metadata.
+    pub decimals: u8,
+}
+
+impl Cis2Token {
+    /// Symbol of the currency of the token: The contract address formatted as
+    /// in account identifiers followed by the hex encoded token ID, separated
+    /// by ':' (e.g. "contract:1234_0:01").
+    pub fn symbol(&self) -> String {
+        format!(
+            "{}:{}",
+            contract_address_string(&self.contract),
+            self.token_id
+        )
+    }
+}
+
+impl FromStr for Cis2Token {
+    type Err = anyhow::Error;
+
+    /// Parse a token in the format "<index>_<subindex>:<token_id>:<decimals>"
+    /// where the token ID is hex encoded (and may be empty).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.strip_prefix(ACCOUNT_CONTRACT_PREFIX).unwrap_or(s);
+        let mut parts = s.split(':');
+        let (Some(contract), Some(token_id), Some(decimals), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(anyhow!(
+                "expected format '<index>_<subindex>:<token_id>:<decimals>'"
+            ));
+        };
+        let (index, subindex) = contract
+            .split_once('_')
+            .ok_or_else(|| anyhow!("invalid contract address '{}'", contract))?;
+        let contract = ContractAddress::new(
+            index
+                .parse()
+                .with_context(|| format!("invalid contract index '{}'", index))?,
+            subindex
+                .parse()
+                .with_context(|| format!("invalid contract subindex '{}'", subindex))?,
+        );
+        let token_id = TokenId::from_str(token_id)
+            .map_err(|err| anyhow!("invalid token ID '{}': {}", token_id, err))?;
+        let decimals = decimals
+            .parse()
+            .with_context(|| format!("invalid number of decimals '{}'", decimals))?;
+        Ok(Self {
+            contract,
+            token_id,
+            decimals,
+        })
+    }
+}
+
+/// The CIS-2 tokens configured to be tracked.
+#[derive(Clone, Default)]
+pub struct Cis2Tokens {
+    tokens: Arc<Vec<Cis2Token>>,
+}
+
+impl Cis2Tokens {
+    /// Parse a comma separated list of tokens (see [`Cis2Token::from_str`]).
+    pub fn parse(s: &str) -> anyhow::Result<Self> {
+        let tokens = s
+            .split(',')
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+            .map(|t| {
+                t.parse()
+                    .with_context(|| format!("invalid CIS-2 token '{}'", t))
+            })
+            .collect::<anyhow::Result<Vec<Cis2Token>>>()?;
+        Ok(Self {
+            tokens: Arc::new(tokens),
+        })
+    }
+
+    /// Whether any tokens of the given contract are tracked.
+    pub fn is_tracked_contract(&self, contract: &ContractAddress) -> bool {
+        self.tokens.iter().any(|t| t.contract == *contract)
+    }
+
+    pub fn get(&self, contract: &ContractAddress, token_id: &TokenId) -> Option<&Cis2Token> {
+        self.tokens
+            .iter()
+            .find(|t| t.contract == *contract && t.token_id == *token_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::amount::amount_from_cis2_units;
+
+    fn token(s: &str) -> Cis2Token {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn token_is_parsed_with_and_without_prefix() {
+        for s in ["1234_5:01ff:6", "contract:1234_5:01ff:6"] {
+            let t = token(s);
+            assert_eq!(t.contract, ContractAddress::new(1234, 5));
+            assert_eq!(t.token_id.to_string(), "01ff");
+            assert_eq!(t.decimals, 6);
+            assert_eq!(t.symbol(), "contract:1234_5:01ff");
+        }
+        let t = token("0_0::0");
+        assert_eq!(t.token_id.to_string(), "");
+        assert_eq!(t.symbol(), "contract:0_0:");
+        assert_eq!(token("1_0:00:255").decimals, 255);
+    }
+
+    #[test]
+    fn malformed_token_is_rejected() {
+        for s in [
+            "",
+            "1_0",
+            "1_0:01",
+            "1_0:01:6:7",
+            "1:01:6",
+            "x_0:01:6",
+            "1_x:01:6",
+            "-1_0:01:6",
+            "18446744073709551616_0:01:6",
+            "1_0:1:6",
+            "1_0:zz:6",
+            "1_0:01:",
+            "1_0:01:-1",
+            "1_0:01:256",
+        ] {
+            assert!(s.parse::<Cis2Token>().is_err(), "token {:?}", s);
+        }
+    }
+
+    #[test]
+    fn token_list_is_parsed() {
+        let tokens = Cis2Tokens::parse(" 1_0:01:6 , ,2_0::0,").unwrap();
+        assert!(tokens.is_tracked_contract(&ContractAddress::new(1, 0)));
+        assert!(tokens.is_tracked_contract(&ContractAddress::new(2, 0)));
+        assert!(!tokens.is_tracked_contract(&ContractAddress::new(1, 1)));
+        let id = TokenId::from_str("01").unwrap();
+        assert_eq!(
+            tokens
+                .get(&ContractAddress::new(1, 0), &id)
+                .unwrap()
+                .decimals,
+            6
+        );
+        assert!(tokens.get(&ContractAddress::new(2, 0), &id).is_none());
+        assert!(Cis2Tokens::parse("").is_ok());
+        assert!(Cis2Tokens::parse("1_0:01:6,1_0:01").is_err());
+    }
+
+    #[test]
+    fn currency_round_trips_through_metadata() {
+        let t = token("7_0:0a:3");
+        let amount = amount_from_cis2_units("-1234".to_string(), &t);
+        // Values are kept in the smallest unit of the token; the decimals are only
+        // part of the currency.
+        assert_eq!(amount.value, "-1234");
+        assert_eq!(amount.currency.symbol, "contract:7_0:0a");
+        assert_eq!(amount.currency.decimals, 3);
+        let (contract, token_id) = cis2_token_from_currency(&amount.currency).unwrap().unwrap();
+        assert_eq!(contract, t.contract);
+        assert_eq!(token_id, t.token_id);
+    }
+
+    #[test]
+    fn currency_with_invalid_metadata_is_rejected() {
+        let currency = |metadata: Option<serde_json::Value>| Currency {
+            symbol: "x".to_string(),
+            decimals: 0,
+            metadata,
+        };
+        assert!(cis2_token_from_currency(&currency(None)).unwrap().is_none());
+        assert!(
+            cis2_token_from_currency(&currency(Some(serde_json::json!({"other": 1}))))
+                .unwrap()
+                .is_none()
+        );
+        for (contract_address, token_id) in [
+            ("contract:1_0", "0"),
+            ("contract:1", "00"),
+            ("baking_reward_account", "00"),
+            ("3rsc7HNLVKnFz9vmKkAaEMVpNkFA4hZxJpZinCtUTJbBh58yYi", "00"),
+        ] {
+            let metadata = serde_json::json!({
+                "contract_address": contract_address,
+                "token_id": token_id,
+            });
+            assert!(matches!(
+                cis2_token_from_currency(&currency(Some(metadata))),
+                Err(ApiError::InvalidCurrency)
+            ));
+        }
+    }
+}
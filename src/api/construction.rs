@@ -3,9 +3,16 @@ use crate::{
     api::{
         amount::{amount_from_uccd, uccd_from_amount},
         error::{ApiError, ApiResult, InvalidSignatureError},
-        query::{Address, account_address_from_identifier},
+        mempool::MempoolTracker,
+        query::{Address, account_address_from_identifier, map_query_result},
+        reject_reason::reject_reason_metadata,
         transaction::{
-            MemoMetadata, OPERATION_TYPE_TRANSFER, transaction_type_from_operation_type,
+            MemoMetadata, OPERATION_TYPE_CONFIGURE_BAKER, OPERATION_TYPE_CONFIGURE_DELEGATION,
+            OPERATION_TYPE_REGISTER_DATA, OPERATION_TYPE_TRANSFER,
+            OPERATION_TYPE_TRANSFER_WITH_SCHEDULE, OPERATION_TYPE_UPDATE_CONTRACT,
+            TimestampedAmount, TransferWithScheduleMetadata, contract_address_string,
+            delegation_target_from_string, delegation_target_to_string, open_status_from_string,
+            open_status_to_string, transaction_type_from_operation_type,
             transaction_type_to_operation_type,
         },
     },
@@ -14,35 +21,58 @@ use concordium_rust_sdk::{
     common::{
         SerdeDeserialize, SerdeSerialize,
         types::{
-            Amount as CCAmount, CredentialIndex, KeyIndex, TransactionSignature, TransactionTime,
+            Amount as CCAmount, CredentialIndex, KeyIndex, Timestamp, TransactionSignature,
+            TransactionTime,
         },
     },
     id::types::AccountAddress,
+    smart_contracts::common::{OwnedParameter, OwnedReceiveName},
     types::{
-        Memo, Nonce, TransactionType,
+        Address as ContractInvoker, AmountFraction, ContractAddress, Energy, Memo, Nonce,
+        RegisteredData, TransactionType, UrlText,
+        hashes::BlockHash,
+        smart_contracts::{ContractContext, InvokeContractResult},
         transactions::{
-            AccountTransaction, BlockItem, EncodedPayload, Payload, PayloadLike, TransactionHeader,
-            compute_transaction_sign_hash, construct, construct::GivenEnergy, cost,
+            AccountTransaction, BlockItem, ConfigureBakerKeysPayload, ConfigureBakerPayload,
+            ConfigureDelegationPayload, EncodedPayload, Payload, PayloadLike, TransactionHeader,
+            UpdateContractPayload, compute_transaction_sign_hash, construct,
+            construct::GivenEnergy, cost,
         },
     },
 };
 use rosetta::models::*;
-use std::{collections::BTreeMap, ops::Deref, str::FromStr};
+use std::{collections::BTreeMap, str::FromStr};
 
 #[derive(Clone)]
 pub struct ConstructionApi {
     network_validator: NetworkValidator,
     query_helper: QueryHelper,
+    mempool_tracker: MempoolTracker,
 }
 
 #[derive(SerdeSerialize, SerdeDeserialize)]
 struct ConstructionOptions {
     sender: AccountAddress,
+    /// Contract invocation of a contract update; used for suggesting the
+    /// energy to spend on executing the contract.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    invocation: Option<ContractInvocationOptions>,
+}
+
+#[derive(SerdeSerialize, SerdeDeserialize)]
+struct ContractInvocationOptions {
+    contract: ContractAddress,
+    receive_name: String,
+    /// Hex encoded parameter of the invocation.
+    parameter: String,
+    amount_uccd: CCAmount,
 }
 
 #[derive(SerdeSerialize)]
 struct MetadataResponseMetadata {
     account_nonce: Nonce,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_energy: Option<u64>,
 }
 
 #[derive(SerdeDeserialize)]
@@ -51,15 +81,133 @@ struct PayloadRequestMetadata {
     signature_count: u32,
     expiry_unix_millis: u64, // using milliseconds for consistency with block timestamp field
     memo: Option<Memo>,
+    max_energy: Option<u64>,
+}
+
+/// Metadata of a 'configure_delegation' operation. The fields are named like
+/// in the metadata of the operations reported by '/block', where each operation
+/// has exactly one of them. Operations constructing a transaction may have any
+/// number of them; fields that are only known once the transaction has been
+/// executed (like 'delegator_id') are ignored.
+#[derive(SerdeSerialize, SerdeDeserialize, Default)]
+struct ConfigureDelegationMetadata {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    new_stake_uccd: Option<CCAmount>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    restake_earnings: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    delegation_target: Option<String>,
+}
+
+/// Metadata of a 'configure_baker' operation. Like for 'configure_delegation',
+/// the fields are named like in the metadata of the operations reported by
+/// '/block' and fields that are only known once the transaction has been
+/// executed are ignored. As the proofs of ownership of the keys cannot be
+/// created without the private keys, new keys have to be provided together
+/// with these proofs in the field 'keys_with_proofs'.
+#[derive(SerdeSerialize, SerdeDeserialize, Default)]
+struct ConfigureBakerMetadata {
+    #[serde(alias = "stake_uccd", skip_serializing_if = "Option::is_none")]
+    new_stake_uccd: Option<CCAmount>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    restake_earnings: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    open_status: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    metadata_url: Option<UrlText>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    transaction_fee_commission: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    baking_reward_commission: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    finalization_reward_commission: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    suspended: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    keys_with_proofs: Option<ConfigureBakerKeysPayload>,
+}
+
+/// Metadata of a 'register_data' operation; the same as reported by '/block'.
+#[derive(SerdeSerialize, SerdeDeserialize)]
+struct RegisterDataMetadata {
+    /// Hex encoded data of at most 256 bytes.
+    data: String,
 }
 
+/// Metadata of the contract's 'update_contract' operation. The fields are named
+/// like in the metadata of the operation reported by '/block', except that the
+/// events are ignored. The maximum amount of energy to spend on executing the
+/// contract is provided in the metadata of '/construction/payloads' (as
+/// suggested by '/construction/metadata').
+#[derive(SerdeSerialize, SerdeDeserialize)]
+struct UpdateContractMetadata {
+    receive_name: String,
+    /// Hex encoded parameter of the invocation.
+    #[serde(default)]
+    parameter: String,
+    /// Rejected if present: The energy is only part of the transaction header,
+    /// so it couldn't be recovered by '/construction/parse'.
+    #[serde(default, skip_serializing)]
+    max_energy: Option<serde_json::Value>,
+}
+
+/// Margin added to the energy used by invoking the contract of a contract
+/// update when suggesting the maximum energy to spend on the update: A
+/// percentage of the used energy plus a constant amount. The state of the
+/// contract (or chain) may change before the update is executed, which may make
+/// it more expensive. Only the energy that is actually used is charged.
+const INVOCATION_ENERGY_MARGIN_PERCENT: u64 = 20;
+const INVOCATION_ENERGY_MARGIN: u64 = 1000;
+
+/// Maximum number of releases of a scheduled transfer.
+const MAX_SCHEDULE_RELEASES: usize = 255;
+
 struct ParsedTransferOperation {
     account_address: AccountAddress,
     amount_uccd: i128,
 }
 
+struct ParsedConfigureDelegationOperation {
+    account_address: AccountAddress,
+    payload: ConfigureDelegationPayload,
+}
+
+struct ParsedConfigureBakerOperation {
+    account_address: AccountAddress,
+    payload: ConfigureBakerPayload,
+}
+
+struct ParsedTransferWithScheduleOperation {
+    transfer: ParsedTransferOperation,
+    /// Release schedule; only present for the receiver.
+    schedule: Option<Vec<(Timestamp, CCAmount)>>,
+}
+
+struct ParsedRegisterDataOperation {
+    account_address: AccountAddress,
+    data: RegisteredData,
+}
+
+struct ParsedUpdateContractOperation {
+    address: Address,
+    amount_uccd: i128,
+    /// Invocation; only present for the contract.
+    invocation: Option<ParsedContractInvocation>,
+}
+
+#[derive(Clone)]
+struct ParsedContractInvocation {
+    receive_name: OwnedReceiveName,
+    parameter: OwnedParameter,
+}
+
 enum ParsedOperation {
     Transfer(ParsedTransferOperation),
+    RegisterData(ParsedRegisterDataOperation),
+    TransferWithSchedule(ParsedTransferWithScheduleOperation),
+    ConfigureDelegation(ParsedConfigureDelegationOperation),
+    ConfigureBaker(ParsedConfigureBakerOperation),
+    UpdateContract(ParsedUpdateContractOperation),
 }
 
 struct ParsedTransferTransaction {
@@ -68,8 +216,54 @@ struct ParsedTransferTransaction {
     amount_uccd: u64,
 }
 
+struct ParsedConfigureDelegationTransaction {
+    sender_address: AccountAddress,
+    payload: ConfigureDelegationPayload,
+}
+
+struct ParsedConfigureBakerTransaction {
+    sender_address: AccountAddress,
+    payload: ConfigureBakerPayload,
+}
+
+struct ParsedTransferWithScheduleTransaction {
+    sender_address: AccountAddress,
+    receiver_address: AccountAddress,
+    schedule: Vec<(Timestamp, CCAmount)>,
+}
+
+struct ParsedRegisterDataTransaction {
+    sender_address: AccountAddress,
+    data: RegisteredData,
+}
+
+struct ParsedUpdateContractTransaction {
+    sender_address: AccountAddress,
+    contract: ContractAddress,
+    amount_uccd: u64,
+    invocation: ParsedContractInvocation,
+}
+
 enum ParsedTransaction {
     Transfer(ParsedTransferTransaction),
+    RegisterData(ParsedRegisterDataTransaction),
+    TransferWithSchedule(ParsedTransferWithScheduleTransaction),
+    ConfigureDelegation(ParsedConfigureDelegationTransaction),
+    ConfigureBaker(ParsedConfigureBakerTransaction),
+    UpdateContract(ParsedUpdateContractTransaction),
+}
+
+impl ParsedTransaction {
+    fn sender_address(&self) -> AccountAddress {
+        match self {
+            ParsedTransaction::Transfer(tx) => tx.sender_address,
+            ParsedTransaction::RegisterData(tx) => tx.sender_address,
+            ParsedTransaction::TransferWithSchedule(tx) => tx.sender_address,
+            ParsedTransaction::ConfigureDelegation(tx) => tx.sender_address,
+            ParsedTransaction::ConfigureBaker(tx) => tx.sender_address,
+            ParsedTransaction::UpdateContract(tx) => tx.sender_address,
+        }
+    }
 }
 
 // TODO Seems redundant - should just use 'AccountTransaction<EncodedPayload>'
@@ -81,10 +275,15 @@ struct UnsignedTransaction {
 }
 
 impl ConstructionApi {
-    pub fn new(network_validator: NetworkValidator, query_helper: QueryHelper) -> Self {
+    pub fn new(
+        network_validator: NetworkValidator,
+        query_helper: QueryHelper,
+        mempool_tracker: MempoolTracker,
+    ) -> Self {
         Self {
             network_validator,
             query_helper,
+            mempool_tracker,
         }
     }
 
@@ -94,10 +293,24 @@ impl ConstructionApi {
     ) -> ApiResult<ConstructionPreprocessResponse> {
         self.network_validator
             .validate_network_identifier(*req.network_identifier)?;
-        let options = match transaction_from_operations(&req.operations)? {
-            ParsedTransaction::Transfer(transfer_tx) => ConstructionOptions {
-                sender: transfer_tx.sender_address,
-            },
+        let parsed_transaction = transaction_from_operations(&req.operations)?;
+        let invocation = match &parsed_transaction {
+            ParsedTransaction::UpdateContract(tx) => Some(ContractInvocationOptions {
+                contract: tx.contract,
+                receive_name: tx
+                    .invocation
+                    .receive_name
+                    .as_receive_name()
+                    .get_chain_name()
+                    .to_string(),
+                parameter: hex::encode(tx.invocation.parameter.as_ref()),
+                amount_uccd: CCAmount::from_micro_ccd(tx.amount_uccd),
+            }),
+            _ => None,
+        };
+        let options = ConstructionOptions {
+            sender: parsed_transaction.sender_address(),
+            invocation,
         };
         Ok(ConstructionPreprocessResponse {
             options: Some(serde_json::to_value(&options).map_err(|err| {
@@ -129,17 +342,76 @@ impl ConstructionApi {
             .query_helper
             .query_account_info_by_address(opts.sender, &consensus_status.last_finalized_block)
             .await?;
+        let max_energy = match opts.invocation {
+            None => None,
+            Some(invocation) => Some(
+                self.invocation_energy(
+                    opts.sender,
+                    invocation,
+                    &consensus_status.last_finalized_block,
+                )
+                .await?,
+            ),
+        };
         // TODO Should include account's credential keys? Would enable signature
         // verification later on.
         Ok(ConstructionMetadataResponse {
             metadata: serde_json::to_value(MetadataResponseMetadata {
                 account_nonce: sender_info.account_nonce,
+                max_energy,
             })
             .unwrap(),
             suggested_fee: None,
         })
     }
 
+    /// Invoke the contract of a contract update on behalf of the sender and
+    /// return the energy used by the invocation plus a margin (see
+    /// 'INVOCATION_ENERGY_MARGIN_PERCENT').
+    async fn invocation_energy(
+        &self,
+        sender: AccountAddress,
+        invocation: ContractInvocationOptions,
+        block_hash: &BlockHash,
+    ) -> ApiResult<u64> {
+        let method = OwnedReceiveName::new(invocation.receive_name)
+            .map_err(|_| ApiError::InvalidConstructionOptions)?;
+        let parameter = hex::decode(invocation.parameter)
+            .ok()
+            .and_then(|bytes| OwnedParameter::try_from(bytes).ok())
+            .ok_or(ApiError::InvalidConstructionOptions)?;
+        let context = ContractContext {
+            invoker: Some(ContractInvoker::Account(sender)),
+            contract: invocation.contract,
+            amount: invocation.amount_uccd,
+            method,
+            parameter,
+            energy: None,
+        };
+        let result = map_query_result(
+            self.query_helper
+                .client
+                .clone()
+                .invoke_instance(*block_hash, &context)
+                .await
+                .map(|x| x.response),
+            ApiError::NoContractsMatched,
+        )?;
+        match result {
+            InvokeContractResult::Success { used_energy, .. } => Ok(used_energy
+                .energy
+                .saturating_add(used_energy.energy * INVOCATION_ENERGY_MARGIN_PERCENT / 100)
+                .saturating_add(INVOCATION_ENERGY_MARGIN)),
+            InvokeContractResult::Failure { reason, .. } => {
+                Err(ApiError::ContractInvocationRejected(
+                    reason
+                        .as_known()
+                        .map(|r| Box::new(reject_reason_metadata(r))),
+                ))
+            }
+        }
+    }
+
     pub async fn payloads(
         &self,
         req: ConstructionPayloadsRequest,
@@ -155,7 +427,8 @@ impl ConstructionApi {
                 .map_err(|_| ApiError::InvalidPayloadsMetadata)?,
         };
         let parsed_transaction = transaction_from_operations(&req.operations)?;
-        let (builder, account_address) = match parsed_transaction {
+        let account_address = parsed_transaction.sender_address();
+        let (payload, energy) = match parsed_transaction {
             ParsedTransaction::Transfer(tx) => {
                 let to_address = tx.receiver_address;
                 let amount = CCAmount::from_micro_ccd(tx.amount_uccd);
@@ -167,19 +440,75 @@ impl ConstructionApi {
                         memo,
                     },
                 };
-                let pre_tx = construct::make_transaction(
-                    tx.sender_address,
-                    metadata.account_nonce,
-                    TransactionTime::from_seconds(metadata.expiry_unix_millis / 1000),
-                    GivenEnergy::Add {
-                        num_sigs: metadata.signature_count,
-                        energy: cost::SIMPLE_TRANSFER,
+                (payload, cost::SIMPLE_TRANSFER)
+            }
+            ParsedTransaction::TransferWithSchedule(tx) => {
+                let to = tx.receiver_address;
+                // Casting is safe as the number of releases is at most 'MAX_SCHEDULE_RELEASES'.
+                let energy = cost::scheduled_transfer(tx.schedule.len() as u16);
+                let schedule = tx.schedule;
+                let payload = match metadata.memo {
+                    None => Payload::TransferWithSchedule { to, schedule },
+                    Some(memo) => Payload::TransferWithScheduleAndMemo { to, memo, schedule },
+                };
+                (payload, energy)
+            }
+            ParsedTransaction::RegisterData(tx) => {
+                if metadata.memo.is_some() {
+                    return Err(ApiError::UnsupportedFieldPresent("memo".to_string()));
+                }
+                (Payload::RegisterData { data: tx.data }, cost::REGISTER_DATA)
+            }
+            ParsedTransaction::ConfigureDelegation(tx) => {
+                if metadata.memo.is_some() {
+                    return Err(ApiError::UnsupportedFieldPresent("memo".to_string()));
+                }
+                (
+                    Payload::ConfigureDelegation { data: tx.payload },
+                    cost::CONFIGURE_DELEGATION,
+                )
+            }
+            ParsedTransaction::ConfigureBaker(tx) => {
+                if metadata.memo.is_some() {
+                    return Err(ApiError::UnsupportedFieldPresent("memo".to_string()));
+                }
+                // Verifying the proofs of the keys makes the transaction more expensive.
+                let energy = if tx.payload.keys_with_proofs.is_some() {
+                    cost::CONFIGURE_BAKER_WITH_KEYS
+                } else {
+                    cost::CONFIGURE_BAKER_WITHOUT_KEYS
+                };
+                (Payload::ConfigureBaker { data: tx.payload }, energy)
+            }
+            ParsedTransaction::UpdateContract(tx) => {
+                if metadata.memo.is_some() {
+                    return Err(ApiError::UnsupportedFieldPresent("memo".to_string()));
+                }
+                let energy = match metadata.max_energy {
+                    None => return Err(ApiError::RequiredFieldMissing("max_energy".to_string())),
+                    Some(e) => Energy::from(e),
+                };
+                let payload = Payload::Update {
+                    payload: UpdateContractPayload {
+                        amount: CCAmount::from_micro_ccd(tx.amount_uccd),
+                        address: tx.contract,
+                        receive_name: tx.invocation.receive_name,
+                        message: tx.invocation.parameter,
                     },
-                    payload,
-                );
-                (pre_tx, tx.sender_address)
+                };
+                (payload, energy)
             }
         };
+        let builder = construct::make_transaction(
+            account_address,
+            metadata.account_nonce,
+            TransactionTime::from_seconds(metadata.expiry_unix_millis / 1000),
+            GivenEnergy::Add {
+                num_sigs: metadata.signature_count,
+                energy,
+            },
+            payload,
+        );
         Ok(ConstructionPayloadsResponse {
             unsigned_transaction: serde_json::to_string(&UnsignedTransaction {
                 header: builder.header.clone(),
@@ -340,13 +669,15 @@ impl ConstructionApi {
         self.network_validator
             .validate_network_identifier(*req.network_identifier)?;
 
-        let block_item = parse_block_item(req.signed_transaction.as_str())?;
+        let signed_tx = decode_signed_transaction(req.signed_transaction.as_str())?;
         let transaction_hash = self
             .query_helper
             .client
             .clone()
-            .send_block_item(&block_item)
+            .send_block_item(&BlockItem::AccountTransaction(signed_tx.clone()))
             .await?;
+        self.mempool_tracker
+            .insert_submitted(transaction_hash, signed_tx);
         Ok(TransactionIdentifierResponse::new(
             TransactionIdentifier::new(transaction_hash.to_string()),
         ))
@@ -369,28 +700,217 @@ fn parse_operation(op: &Operation) -> ApiResult<ParsedOperation> {
     match transaction_type_from_operation_type(op._type.as_str()) {
         Ok(TransactionType::Transfer) => {
             // Covers transfers with and without memo.
+            Ok(ParsedOperation::Transfer(parse_transfer_operation(op)?))
+        }
+        Ok(TransactionType::TransferWithSchedule) => {
+            // Covers scheduled transfers with and without memo.
+            let transfer = parse_transfer_operation(op)?;
+            let schedule = match op.metadata.clone() {
+                None => None,
+                Some(v) => Some(
+                    serde_json::from_value::<TransferWithScheduleMetadata>(v)
+                        .map_err(|err| {
+                            ApiError::InvalidOperationMetadata(op._type.clone(), err.to_string())
+                        })?
+                        .amounts
+                        .into_iter()
+                        .map(|a| (a.timestamp, a.amount_uccd))
+                        .collect(),
+                ),
+            };
+            Ok(ParsedOperation::TransferWithSchedule(
+                ParsedTransferWithScheduleOperation { transfer, schedule },
+            ))
+        }
+        Ok(TransactionType::RegisterData) => {
+            // The amount may be omitted or zero as no CCD is transferred.
+            if let Some(a) = op.amount.as_deref() {
+                if uccd_from_amount(a)? != 0 {
+                    return Err(ApiError::InconsistentOperations(
+                        "amount of register_data operation must be zero".to_string(),
+                    ));
+                }
+            }
+            let account_address = operation_account_address(op)?;
+            let metadata = operation_metadata::<RegisterDataMetadata>(op)?;
+            let invalid_metadata =
+                |msg: String| ApiError::InvalidOperationMetadata(op._type.clone(), msg);
+            let bytes = hex::decode(metadata.data.as_str())
+                .map_err(|err| invalid_metadata(format!("invalid hex data: {}", err)))?;
+            let data = RegisteredData::try_from(bytes)
+                .map_err(|_| invalid_metadata("data must be at most 256 bytes".to_string()))?;
+            Ok(ParsedOperation::RegisterData(ParsedRegisterDataOperation {
+                account_address,
+                data,
+            }))
+        }
+        Ok(TransactionType::ConfigureDelegation) => {
+            if op.amount.is_some() {
+                return Err(ApiError::UnsupportedFieldPresent("amount".to_string()));
+            }
+            let account_address = operation_account_address(op)?;
+            let metadata = operation_metadata::<ConfigureDelegationMetadata>(op)?;
+            let delegation_target = match metadata.delegation_target {
+                None => None,
+                Some(t) => Some(delegation_target_from_string(t.as_str()).ok_or_else(|| {
+                    ApiError::InvalidOperationMetadata(
+                        op._type.clone(),
+                        format!("invalid delegation target '{}'", t),
+                    )
+                })?),
+            };
+            Ok(ParsedOperation::ConfigureDelegation(
+                ParsedConfigureDelegationOperation {
+                    account_address,
+                    payload: ConfigureDelegationPayload {
+                        capital: metadata.new_stake_uccd,
+                        restake_earnings: metadata.restake_earnings,
+                        delegation_target,
+                    },
+                },
+            ))
+        }
+        Ok(TransactionType::ConfigureBaker) => {
+            if op.amount.is_some() {
+                return Err(ApiError::UnsupportedFieldPresent("amount".to_string()));
+            }
+            let account_address = operation_account_address(op)?;
+            let metadata = operation_metadata::<ConfigureBakerMetadata>(op)?;
+            let invalid_metadata =
+                |msg: String| ApiError::InvalidOperationMetadata(op._type.clone(), msg);
+            let open_for_delegation = match metadata.open_status {
+                None => None,
+                Some(s) => Some(
+                    open_status_from_string(s.as_str())
+                        .ok_or_else(|| invalid_metadata(format!("invalid open status '{}'", s)))?,
+                ),
+            };
+            let commission = |rate: Option<String>, name: &str| match rate {
+                None => Ok(None),
+                Some(r) => amount_fraction_from_string(r.as_str())
+                    .map(Some)
+                    .ok_or_else(|| invalid_metadata(format!("invalid {} '{}'", name, r))),
+            };
+            Ok(ParsedOperation::ConfigureBaker(
+                ParsedConfigureBakerOperation {
+                    account_address,
+                    payload: ConfigureBakerPayload {
+                        capital: metadata.new_stake_uccd,
+                        restake_earnings: metadata.restake_earnings,
+                        open_for_delegation,
+                        keys_with_proofs: metadata.keys_with_proofs,
+                        metadata_url: metadata.metadata_url,
+                        transaction_fee_commission: commission(
+                            metadata.transaction_fee_commission,
+                            "transaction fee commission",
+                        )?,
+                        baking_reward_commission: commission(
+                            metadata.baking_reward_commission,
+                            "baking reward commission",
+                        )?,
+                        finalization_reward_commission: commission(
+                            metadata.finalization_reward_commission,
+                            "finalization reward commission",
+                        )?,
+                        suspend: metadata.suspended,
+                    },
+                },
+            ))
+        }
+        Ok(TransactionType::Update) => {
             let amount_uccd = match op.amount.as_deref() {
                 Some(a) => uccd_from_amount(a),
                 None => Err(ApiError::RequiredFieldMissing("amount".to_string())),
             }?;
-            let account_address = match op.account.clone() {
-                None => Err(ApiError::RequiredFieldMissing("account".to_string())),
-                Some(a) => match account_address_from_identifier(a.deref())? {
-                    Address::Account(addr) => Ok(addr),
-                    _ => Err(ApiError::InvalidAccountAddress(
-                        a.deref().address.to_string(),
-                    )),
+            let address = match op.account.as_deref() {
+                None => return Err(ApiError::RequiredFieldMissing("account".to_string())),
+                Some(a) => account_address_from_identifier(a)?,
+            };
+            // Only the contract's operation describes the invocation.
+            let invocation = match address {
+                Address::Contract(_) => Some(parse_contract_invocation(op)?),
+                _ => None,
+            };
+            Ok(ParsedOperation::UpdateContract(
+                ParsedUpdateContractOperation {
+                    address,
+                    amount_uccd,
+                    invocation,
                 },
-            }?;
-            Ok(ParsedOperation::Transfer(ParsedTransferOperation {
-                account_address,
-                amount_uccd,
-            }))
+            ))
         }
         _ => Err(ApiError::UnsupportedOperationType(op._type.clone())),
     }
 }
 
+fn parse_contract_invocation(op: &Operation) -> ApiResult<ParsedContractInvocation> {
+    let metadata = operation_metadata::<UpdateContractMetadata>(op)?;
+    if metadata.max_energy.is_some() {
+        return Err(ApiError::UnsupportedFieldPresent("max_energy".to_string()));
+    }
+    let invalid_metadata = |msg: String| ApiError::InvalidOperationMetadata(op._type.clone(), msg);
+    let receive_name = OwnedReceiveName::new(metadata.receive_name.clone()).map_err(|err| {
+        invalid_metadata(format!(
+            "invalid receive name '{}': {}",
+            metadata.receive_name, err
+        ))
+    })?;
+    let parameter_bytes = hex::decode(metadata.parameter.as_str())
+        .map_err(|err| invalid_metadata(format!("invalid hex parameter: {}", err)))?;
+    let parameter = OwnedParameter::try_from(parameter_bytes)
+        .map_err(|err| invalid_metadata(format!("invalid parameter: {}", err)))?;
+    Ok(ParsedContractInvocation {
+        receive_name,
+        parameter,
+    })
+}
+
+fn parse_transfer_operation(op: &Operation) -> ApiResult<ParsedTransferOperation> {
+    let amount_uccd = match op.amount.as_deref() {
+        Some(a) => uccd_from_amount(a),
+        None => Err(ApiError::RequiredFieldMissing("amount".to_string())),
+    }?;
+    let account_address = operation_account_address(op)?;
+    Ok(ParsedTransferOperation {
+        account_address,
+        amount_uccd,
+    })
+}
+
+/// Parse a commission rate formatted as a decimal fraction (e.g. "0.05") with
+/// a precision of up to 5 decimals.
+fn amount_fraction_from_string(rate: &str) -> Option<AmountFraction> {
+    let (integer, fraction) = rate.split_once('.').unwrap_or((rate, ""));
+    let fraction = fraction.trim_end_matches('0');
+    let digits = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+    if !digits(integer) || (!fraction.is_empty() && !digits(fraction)) || fraction.len() > 5 {
+        return None;
+    }
+    let integer: u32 = integer.parse().ok()?;
+    let fraction: u32 = format!("{:0<5}", fraction).parse().ok()?;
+    AmountFraction::new(integer.checked_mul(100_000)?.checked_add(fraction)?)
+}
+
+/// Resolve the (non-virtual) account of an operation.
+fn operation_account_address(op: &Operation) -> ApiResult<AccountAddress> {
+    match op.account.as_deref() {
+        None => Err(ApiError::RequiredFieldMissing("account".to_string())),
+        Some(a) => match account_address_from_identifier(a)? {
+            Address::Account(addr) => Ok(addr),
+            _ => Err(ApiError::InvalidAccountAddress(a.address.to_string())),
+        },
+    }
+}
+
+/// Decode the (required) metadata of an operation.
+fn operation_metadata<T: serde::de::DeserializeOwned>(op: &Operation) -> ApiResult<T> {
+    match op.metadata.clone() {
+        None => Err(ApiError::RequiredFieldMissing("metadata".to_string())),
+        Some(v) => serde_json::from_value(v)
+            .map_err(|err| ApiError::InvalidOperationMetadata(op._type.clone(), err.to_string())),
+    }
+}
+
 fn parse_operations(ops: &[Operation]) -> ApiResult<Vec<ParsedOperation>> {
     ops.iter().map(parse_operation).collect()
 }
@@ -401,6 +921,24 @@ fn parse_transaction(ops: &[ParsedOperation]) -> ApiResult<ParsedTransaction> {
             ParsedOperation::Transfer(sender),
             ParsedOperation::Transfer(receiver),
         ] => parse_transfer_transaction(sender, receiver),
+        [
+            ParsedOperation::TransferWithSchedule(sender),
+            ParsedOperation::TransferWithSchedule(receiver),
+        ] => parse_transfer_with_schedule_transaction(sender, receiver),
+        [ParsedOperation::RegisterData(op)] => Ok(ParsedTransaction::RegisterData(
+            ParsedRegisterDataTransaction {
+                sender_address: op.account_address,
+                data: op.data.clone(),
+            },
+        )),
+        [ParsedOperation::ConfigureDelegation(_), ..] => {
+            parse_configure_delegation_transaction(ops)
+        }
+        [ParsedOperation::ConfigureBaker(_), ..] => parse_configure_baker_transaction(ops),
+        [
+            ParsedOperation::UpdateContract(sender),
+            ParsedOperation::UpdateContract(contract),
+        ] => parse_update_contract_transaction(sender, contract),
         _ => Err(ApiError::InconsistentOperations(
             "invalid type or number of operations".to_string(),
         )),
@@ -411,6 +949,19 @@ fn parse_transfer_transaction(
     sender: &ParsedTransferOperation,
     receiver: &ParsedTransferOperation,
 ) -> Result<ParsedTransaction, ApiError> {
+    Ok(ParsedTransaction::Transfer(ParsedTransferTransaction {
+        sender_address: sender.account_address,
+        receiver_address: receiver.account_address,
+        amount_uccd: transfer_amount(sender, receiver)?,
+    }))
+}
+
+/// Validate that the amounts of the sender and receiver operations of a
+/// transfer sum to zero and return the transferred amount.
+fn transfer_amount(
+    sender: &ParsedTransferOperation,
+    receiver: &ParsedTransferOperation,
+) -> ApiResult<u64> {
     if sender.amount_uccd >= 0 {
         return Err(ApiError::InconsistentOperations(
             "amount in first transfer operation must be negative".to_string(),
@@ -426,11 +977,248 @@ fn parse_transfer_transaction(
             "amount in transfer operations must sum to zero".to_string(),
         ));
     }
-    Ok(ParsedTransaction::Transfer(ParsedTransferTransaction {
-        sender_address: sender.account_address,
-        receiver_address: receiver.account_address,
-        amount_uccd: receiver.amount_uccd as u64, // casting from positive i64 to u64
-    }))
+    Ok(receiver.amount_uccd as u64) // casting from positive i64 to u64
+}
+
+fn parse_transfer_with_schedule_transaction(
+    sender: &ParsedTransferWithScheduleOperation,
+    receiver: &ParsedTransferWithScheduleOperation,
+) -> ApiResult<ParsedTransaction> {
+    let amount_uccd = transfer_amount(&sender.transfer, &receiver.transfer)?;
+    if sender.schedule.is_some() {
+        return Err(ApiError::InconsistentOperations(
+            "release schedule must be in the metadata of the second operation".to_string(),
+        ));
+    }
+    let Some(schedule) = receiver.schedule.clone() else {
+        return Err(ApiError::RequiredFieldMissing("metadata".to_string()));
+    };
+    if schedule.is_empty() || schedule.len() > MAX_SCHEDULE_RELEASES {
+        return Err(ApiError::InconsistentOperations(format!(
+            "release schedule must have between 1 and {} releases",
+            MAX_SCHEDULE_RELEASES
+        )));
+    }
+    if schedule.windows(2).any(|w| w[0].0 >= w[1].0) {
+        return Err(ApiError::InconsistentOperations(
+            "release timestamps must be strictly increasing".to_string(),
+        ));
+    }
+    if schedule.iter().any(|(_, a)| a.micro_ccd() == 0) {
+        return Err(ApiError::InconsistentOperations(
+            "release amounts must be positive".to_string(),
+        ));
+    }
+    let schedule_sum = schedule
+        .iter()
+        .try_fold(0u64, |sum, (_, a)| sum.checked_add(a.micro_ccd()));
+    if schedule_sum != Some(amount_uccd) {
+        return Err(ApiError::InconsistentOperations(
+            "release amounts must sum to the transferred amount".to_string(),
+        ));
+    }
+    Ok(ParsedTransaction::TransferWithSchedule(
+        ParsedTransferWithScheduleTransaction {
+            sender_address: sender.transfer.account_address,
+            receiver_address: receiver.transfer.account_address,
+            schedule,
+        },
+    ))
+}
+
+fn parse_update_contract_transaction(
+    sender: &ParsedUpdateContractOperation,
+    contract: &ParsedUpdateContractOperation,
+) -> ApiResult<ParsedTransaction> {
+    let Address::Account(sender_address) = sender.address else {
+        return Err(ApiError::InconsistentOperations(
+            "account of first update_contract operation must be the sending account".to_string(),
+        ));
+    };
+    let (Address::Contract(contract_address), Some(invocation)) =
+        (&contract.address, &contract.invocation)
+    else {
+        return Err(ApiError::InconsistentOperations(
+            "account of second update_contract operation must be the contract".to_string(),
+        ));
+    };
+    // Contrary to transfers, the amount may be zero.
+    if sender.amount_uccd > 0 {
+        return Err(ApiError::InconsistentOperations(
+            "amount in first update_contract operation must not be positive".to_string(),
+        ));
+    }
+    if sender.amount_uccd != -contract.amount_uccd {
+        return Err(ApiError::InconsistentOperations(
+            "amount in update_contract operations must sum to zero".to_string(),
+        ));
+    }
+    let amount_uccd = u64::try_from(contract.amount_uccd)
+        .map_err(|_| ApiError::InvalidAmount(contract.amount_uccd.to_string()))?;
+    Ok(ParsedTransaction::UpdateContract(
+        ParsedUpdateContractTransaction {
+            sender_address,
+            contract: *contract_address,
+            amount_uccd,
+            invocation: invocation.clone(),
+        },
+    ))
+}
+
+/// Merge the fields of one or more 'configure_delegation' operations of the
+/// same account into a single transaction. Each field may only be set once.
+fn parse_configure_delegation_transaction(ops: &[ParsedOperation]) -> ApiResult<ParsedTransaction> {
+    let mut sender_address = None;
+    let mut payload = ConfigureDelegationPayload {
+        capital: None,
+        restake_earnings: None,
+        delegation_target: None,
+    };
+    for op in ops {
+        let ParsedOperation::ConfigureDelegation(op) = op else {
+            return Err(ApiError::InconsistentOperations(
+                "operations must all be of type 'configure_delegation'".to_string(),
+            ));
+        };
+        if *sender_address.get_or_insert(op.account_address) != op.account_address {
+            return Err(ApiError::InconsistentOperations(
+                "operations must all have the same account".to_string(),
+            ));
+        }
+        set_once(&mut payload.capital, op.payload.capital, "new_stake_uccd")?;
+        set_once(
+            &mut payload.restake_earnings,
+            op.payload.restake_earnings,
+            "restake_earnings",
+        )?;
+        set_once(
+            &mut payload.delegation_target,
+            op.payload.delegation_target,
+            "delegation_target",
+        )?;
+    }
+    if payload.capital.is_none()
+        && payload.restake_earnings.is_none()
+        && payload.delegation_target.is_none()
+    {
+        return Err(ApiError::InconsistentOperations(
+            "operations must set at least one delegation field".to_string(),
+        ));
+    }
+    Ok(ParsedTransaction::ConfigureDelegation(
+        ParsedConfigureDelegationTransaction {
+            // Defined as there is at least one operation.
+            sender_address: sender_address.unwrap(),
+            payload,
+        },
+    ))
+}
+
+/// Merge the fields of one or more 'configure_baker' operations of the same
+/// account into a single transaction. Each field may only be set once.
+fn parse_configure_baker_transaction(ops: &[ParsedOperation]) -> ApiResult<ParsedTransaction> {
+    let mut sender_address = None;
+    let mut payload = ConfigureBakerPayload {
+        capital: None,
+        restake_earnings: None,
+        open_for_delegation: None,
+        keys_with_proofs: None,
+        metadata_url: None,
+        transaction_fee_commission: None,
+        baking_reward_commission: None,
+        finalization_reward_commission: None,
+        suspend: None,
+    };
+    for op in ops {
+        let ParsedOperation::ConfigureBaker(op) = op else {
+            return Err(ApiError::InconsistentOperations(
+                "operations must all be of type 'configure_baker'".to_string(),
+            ));
+        };
+        if *sender_address.get_or_insert(op.account_address) != op.account_address {
+            return Err(ApiError::InconsistentOperations(
+                "operations must all have the same account".to_string(),
+            ));
+        }
+        let p = &op.payload;
+        set_once(&mut payload.capital, p.capital, "new_stake_uccd")?;
+        set_once(
+            &mut payload.restake_earnings,
+            p.restake_earnings,
+            "restake_earnings",
+        )?;
+        set_once(
+            &mut payload.open_for_delegation,
+            p.open_for_delegation,
+            "open_status",
+        )?;
+        set_once(
+            &mut payload.keys_with_proofs,
+            p.keys_with_proofs.clone(),
+            "keys_with_proofs",
+        )?;
+        set_once(
+            &mut payload.metadata_url,
+            p.metadata_url.clone(),
+            "metadata_url",
+        )?;
+        set_once(
+            &mut payload.transaction_fee_commission,
+            p.transaction_fee_commission,
+            "transaction_fee_commission",
+        )?;
+        set_once(
+            &mut payload.baking_reward_commission,
+            p.baking_reward_commission,
+            "baking_reward_commission",
+        )?;
+        set_once(
+            &mut payload.finalization_reward_commission,
+            p.finalization_reward_commission,
+            "finalization_reward_commission",
+        )?;
+        set_once(&mut payload.suspend, p.suspend, "suspended")?;
+    }
+    if matches!(
+        payload,
+        ConfigureBakerPayload {
+            capital: None,
+            restake_earnings: None,
+            open_for_delegation: None,
+            keys_with_proofs: None,
+            metadata_url: None,
+            transaction_fee_commission: None,
+            baking_reward_commission: None,
+            finalization_reward_commission: None,
+            suspend: None,
+        }
+    ) {
+        return Err(ApiError::InconsistentOperations(
+            "operations must set at least one validator field".to_string(),
+        ));
+    }
+    Ok(ParsedTransaction::ConfigureBaker(
+        ParsedConfigureBakerTransaction {
+            // Defined as there is at least one operation.
+            sender_address: sender_address.unwrap(),
+            payload,
+        },
+    ))
+}
+
+/// Set a field of a transaction from an operation unless another operation
+/// already set it.
+fn set_once<T>(field: &mut Option<T>, value: Option<T>, name: &str) -> ApiResult<()> {
+    if value.is_some() {
+        if field.is_some() {
+            return Err(ApiError::InconsistentOperations(format!(
+                "field '{}' is set by multiple operations",
+                name
+            )));
+        }
+        *field = value;
+    }
+    Ok(())
 }
 
 fn transaction_from_operations(ops: &[Operation]) -> ApiResult<ParsedTransaction> {
@@ -455,7 +1243,7 @@ fn decode_signed_transaction(
         .map_err(|_| ApiError::InvalidSignedTransaction)
 }
 
-fn operations_from_transaction(
+pub fn operations_from_transaction(
     header: &TransactionHeader,
     payload: &Payload,
 ) -> ApiResult<(Vec<Operation>, Option<Memo>)> {
@@ -476,6 +1264,47 @@ fn operations_from_transaction(
             amount.micro_ccd() as i128,
             Some(memo.clone()),
         ),
+        Payload::TransferWithSchedule { to, schedule } => {
+            operations_from_transfer_with_schedule_transaction(&header.sender, to, schedule, None)
+        }
+        Payload::TransferWithScheduleAndMemo { to, memo, schedule } => {
+            operations_from_transfer_with_schedule_transaction(
+                &header.sender,
+                to,
+                schedule,
+                Some(memo.clone()),
+            )
+        }
+        Payload::RegisterData { data } => Ok((
+            vec![Operation {
+                operation_identifier: Box::new(OperationIdentifier::new(0)),
+                related_operations: None,
+                _type: OPERATION_TYPE_REGISTER_DATA.to_string(),
+                status: None,
+                account: Some(Box::new(AccountIdentifier::new(header.sender.to_string()))),
+                amount: None,
+                coin_change: None,
+                metadata: Some(
+                    serde_json::to_value(RegisterDataMetadata {
+                        data: hex::encode(data.as_ref()),
+                    })
+                    .unwrap(),
+                ),
+            }],
+            None,
+        )),
+        Payload::ConfigureDelegation { data } => Ok((
+            operations_from_configure_delegation_transaction(&header.sender, data),
+            None,
+        )),
+        Payload::ConfigureBaker { data } => Ok((
+            operations_from_configure_baker_transaction(&header.sender, data),
+            None,
+        )),
+        Payload::Update { payload } => Ok((
+            operations_from_update_contract_transaction(&header.sender, payload),
+            None,
+        )),
         _ => Err(ApiError::UnsupportedOperationType(
             transaction_type_to_operation_type(Some(payload.transaction_type())),
         )),
@@ -514,3 +1343,384 @@ fn operations_from_transfer_transaction(
         memo,
     ))
 }
+
+/// Like a transfer, but with operations of type 'transfer_with_schedule' and
+/// the release schedule in the metadata of the receiver's operation.
+fn operations_from_transfer_with_schedule_transaction(
+    sender_addr: &AccountAddress,
+    receiver_addr: &AccountAddress,
+    schedule: &[(Timestamp, CCAmount)],
+    memo: Option<Memo>,
+) -> ApiResult<(Vec<Operation>, Option<Memo>)> {
+    let amount_uccd = schedule.iter().map(|(_, a)| a.micro_ccd() as i128).sum();
+    let (mut operations, memo) =
+        operations_from_transfer_transaction(sender_addr, receiver_addr, amount_uccd, memo)?;
+    for op in operations.iter_mut() {
+        op._type = OPERATION_TYPE_TRANSFER_WITH_SCHEDULE.to_string();
+    }
+    operations[1].metadata = Some(
+        serde_json::to_value(TransferWithScheduleMetadata {
+            amounts: schedule
+                .iter()
+                .map(|(timestamp, amount)| TimestampedAmount {
+                    timestamp: *timestamp,
+                    amount_uccd: *amount,
+                })
+                .collect(),
+        })
+        .unwrap(),
+    );
+    Ok((operations, memo))
+}
+
+/// One 'configure_delegation' operation per field of the payload that is set.
+fn operations_from_configure_delegation_transaction(
+    sender_addr: &AccountAddress,
+    payload: &ConfigureDelegationPayload,
+) -> Vec<Operation> {
+    let mut metadatas = vec![];
+    if let Some(capital) = payload.capital {
+        metadatas.push(ConfigureDelegationMetadata {
+            new_stake_uccd: Some(capital),
+            ..Default::default()
+        });
+    }
+    if let Some(restake_earnings) = payload.restake_earnings {
+        metadatas.push(ConfigureDelegationMetadata {
+            restake_earnings: Some(restake_earnings),
+            ..Default::default()
+        });
+    }
+    if let Some(target) = &payload.delegation_target {
+        metadatas.push(ConfigureDelegationMetadata {
+            delegation_target: Some(delegation_target_to_string(target)),
+            ..Default::default()
+        });
+    }
+    metadatas
+        .iter()
+        .enumerate()
+        .map(|(i, metadata)| Operation {
+            operation_identifier: Box::new(OperationIdentifier::new(i as i64)),
+            related_operations: None,
+            _type: OPERATION_TYPE_CONFIGURE_DELEGATION.to_string(),
+            status: None,
+            account: Some(Box::new(AccountIdentifier::new(sender_addr.to_string()))),
+            amount: None,
+            coin_change: None,
+            metadata: Some(serde_json::to_value(metadata).unwrap()),
+        })
+        .collect()
+}
+
+/// One 'configure_baker' operation per field of the payload that is set.
+fn operations_from_configure_baker_transaction(
+    sender_addr: &AccountAddress,
+    payload: &ConfigureBakerPayload,
+) -> Vec<Operation> {
+    let mut metadatas = vec![];
+    if let Some(capital) = payload.capital {
+        metadatas.push(ConfigureBakerMetadata {
+            new_stake_uccd: Some(capital),
+            ..Default::default()
+        });
+    }
+    if let Some(restake_earnings) = payload.restake_earnings {
+        metadatas.push(ConfigureBakerMetadata {
+            restake_earnings: Some(restake_earnings),
+            ..Default::default()
+        });
+    }
+    if let Some(open_status) = &payload.open_for_delegation {
+        metadatas.push(ConfigureBakerMetadata {
+            open_status: Some(open_status_to_string(open_status).to_string()),
+            ..Default::default()
+        });
+    }
+    if let Some(keys_with_proofs) = &payload.keys_with_proofs {
+        metadatas.push(ConfigureBakerMetadata {
+            keys_with_proofs: Some(keys_with_proofs.clone()),
+            ..Default::default()
+        });
+    }
+    if let Some(metadata_url) = &payload.metadata_url {
+        metadatas.push(ConfigureBakerMetadata {
+            metadata_url: Some(metadata_url.clone()),
+            ..Default::default()
+        });
+    }
+    if let Some(rate) = payload.transaction_fee_commission {
+        metadatas.push(ConfigureBakerMetadata {
+            transaction_fee_commission: Some(rate.to_string()),
+            ..Default::default()
+        });
+    }
+    if let Some(rate) = payload.baking_reward_commission {
+        metadatas.push(ConfigureBakerMetadata {
+            baking_reward_commission: Some(rate.to_string()),
+            ..Default::default()
+        });
+    }
+    if let Some(rate) = payload.finalization_reward_commission {
+        metadatas.push(ConfigureBakerMetadata {
+            finalization_reward_commission: Some(rate.to_string()),
+            ..Default::default()
+        });
+    }
+    if let Some(suspend) = payload.suspend {
+        metadatas.push(ConfigureBakerMetadata {
+            suspended: Some(suspend),
+            ..Default::default()
+        });
+    }
+    metadatas
+        .iter()
+        .enumerate()
+        .map(|(i, metadata)| Operation {
+            operation_identifier: Box::new(OperationIdentifier::new(i as i64)),
+            related_operations: None,
+            _type: OPERATION_TYPE_CONFIGURE_BAKER.to_string(),
+            status: None,
+            account: Some(Box::new(AccountIdentifier::new(sender_addr.to_string()))),
+            amount: None,
+            coin_change: None,
+            metadata: Some(serde_json::to_value(metadata).unwrap()),
+        })
+        .collect()
+}
+
+/// Like a transfer to the contract, but with operations of type
+/// 'update_contract' and the invocation in the metadata of the contract's
+/// operation. The maximum amount of energy is only part of the transaction
+/// header (together with the base cost of the transaction) and is therefore
+/// omitted.
+fn operations_from_update_contract_transaction(
+    sender_addr: &AccountAddress,
+    payload: &UpdateContractPayload,
+) -> Vec<Operation> {
+    let amount_uccd = payload.amount.micro_ccd() as i128;
+    vec![
+        Operation {
+            operation_identifier: Box::new(OperationIdentifier::new(0)),
+            related_operations: None,
+            _type: OPERATION_TYPE_UPDATE_CONTRACT.to_string(),
+            status: None,
+            account: Some(Box::new(AccountIdentifier::new(sender_addr.to_string()))),
+            amount: Some(Box::new(amount_from_uccd(-amount_uccd))),
+            coin_change: None,
+            metadata: None,
+        },
+        Operation {
+            operation_identifier: Box::new(OperationIdentifier::new(1)),
+            related_operations: None,
+            _type: OPERATION_TYPE_UPDATE_CONTRACT.to_string(),
+            status: None,
+            account: Some(Box::new(AccountIdentifier::new(contract_address_string(
+                &payload.address,
+            )))),
+            amount: Some(Box::new(amount_from_uccd(amount_uccd))),
+            coin_change: None,
+            metadata: Some(
+                serde_json::to_value(UpdateContractMetadata {
+                    receive_name: payload
+                        .receive_name
+                        .as_receive_name()
+                        .get_chain_name()
+                        .to_string(),
+                    parameter: hex::encode(payload.message.as_ref()),
+                    max_energy: None,
+                })
+                .unwrap(),
+            ),
+        },
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn amount_fraction_from_string_accepts_up_to_five_decimals() {
+        assert_eq!(amount_fraction_from_string("0"), AmountFraction::new(0));
+        assert_eq!(
+            amount_fraction_from_string("0.1"),
+            AmountFraction::new(10_000)
+        );
+        assert_eq!(
+            amount_fraction_from_string("0.00001"),
+            AmountFraction::new(1)
+        );
+        assert_eq!(
+            amount_fraction_from_string("0.12345"),
+            AmountFraction::new(12_345)
+        );
+        assert_eq!(
+            amount_fraction_from_string("0.100000"),
+            AmountFraction::new(10_000)
+        );
+        assert_eq!(
+            amount_fraction_from_string("1."),
+            AmountFraction::new(100_000)
+        );
+        assert_eq!(
+            amount_fraction_from_string("1.0"),
+            AmountFraction::new(100_000)
+        );
+        assert_eq!(
+            amount_fraction_from_string("1"),
+            AmountFraction::new(100_000)
+        );
+    }
+
+    #[test]
+    fn amount_fraction_from_string_rejects_malformed_input() {
+        for rate in [
+            "", ".", ".5", "-0.1", "+0.1", " 0.1", "0.1 ", "0,1", "1e-1", "0x1", "0.1.2", "0.-1",
+        ] {
+            assert_eq!(amount_fraction_from_string(rate), None, "rate {:?}", rate);
+        }
+    }
+
+    #[test]
+    fn amount_fraction_from_string_rejects_excess_precision_and_range() {
+        assert_eq!(amount_fraction_from_string("0.000001"), None);
+        assert_eq!(amount_fraction_from_string("0.123451"), None);
+        assert_eq!(amount_fraction_from_string("1.00001"), None);
+        assert_eq!(amount_fraction_from_string("2"), None);
+        // Overflows when scaled to parts per hundred thousand.
+        assert_eq!(amount_fraction_from_string("42949.67296"), None);
+        // Doesn't fit in 32 bits.
+        assert_eq!(amount_fraction_from_string("4294967296"), None);
+        assert_eq!(amount_fraction_from_string("99999999999999999999"), None);
+    }
+
+    const SENDER: AccountAddress = AccountAddress([1; 32]);
+    const RECEIVER: AccountAddress = AccountAddress([2; 32]);
+
+    fn schedule_operation(
+        account_address: AccountAddress,
+        amount_uccd: i128,
+        schedule: Option<&[(u64, u64)]>,
+    ) -> ParsedTransferWithScheduleOperation {
+        ParsedTransferWithScheduleOperation {
+            transfer: ParsedTransferOperation {
+                account_address,
+                amount_uccd,
+            },
+            schedule: schedule.map(|s| {
+                s.iter()
+                    .map(|&(millis, uccd)| {
+                        (
+                            Timestamp::from_timestamp_millis(millis),
+                            CCAmount::from_micro_ccd(uccd),
+                        )
+                    })
+                    .collect()
+            }),
+        }
+    }
+
+    fn parse_schedule(amount_uccd: u64, schedule: &[(u64, u64)]) -> ApiResult<ParsedTransaction> {
+        parse_transfer_with_schedule_transaction(
+            &schedule_operation(SENDER, -i128::from(amount_uccd), None),
+            &schedule_operation(RECEIVER, i128::from(amount_uccd), Some(schedule)),
+        )
+    }
+
+    fn assert_inconsistent(res: ApiResult<ParsedTransaction>, expected: &str) {
+        match res {
+            Err(ApiError::InconsistentOperations(msg)) => {
+                assert!(msg.contains(expected), "unexpected error '{}'", msg)
+            }
+            Err(err) => panic!("unexpected error '{}'", err),
+            Ok(_) => panic!("expected error containing '{}'", expected),
+        }
+    }
+
+    #[test]
+    fn schedule_is_parsed_in_order() {
+        let res = parse_schedule(6, &[(1000, 1), (2000, 2), (3000, 3)]);
+        let Ok(ParsedTransaction::TransferWithSchedule(tx)) = res else {
+            panic!("expected scheduled transfer");
+        };
+        assert_eq!(tx.sender_address, SENDER);
+        assert_eq!(tx.receiver_address, RECEIVER);
+        let releases: Vec<_> = tx
+            .schedule
+            .iter()
+            .map(|(t, a)| (t.millis, a.micro_ccd()))
+            .collect();
+        assert_eq!(releases, vec![(1000, 1), (2000, 2), (3000, 3)]);
+    }
+
+    #[test]
+    fn schedule_timestamps_must_be_strictly_increasing() {
+        assert_inconsistent(
+            parse_schedule(3, &[(2000, 1), (1000, 2)]),
+            "strictly increasing",
+        );
+        assert_inconsistent(
+            parse_schedule(3, &[(1000, 1), (1000, 2)]),
+            "strictly increasing",
+        );
+        assert_inconsistent(
+            parse_schedule(6, &[(1000, 1), (3000, 2), (2000, 3)]),
+            "strictly increasing",
+        );
+    }
+
+    #[test]
+    fn schedule_release_count_is_bounded() {
+        assert_inconsistent(parse_schedule(1, &[]), "between 1 and 255 releases");
+        let max: Vec<_> = (1..=MAX_SCHEDULE_RELEASES as u64).map(|i| (i, 1)).collect();
+        assert!(parse_schedule(max.len() as u64, &max).is_ok());
+        let too_many: Vec<_> = (1..=MAX_SCHEDULE_RELEASES as u64 + 1)
+            .map(|i| (i, 1))
+            .collect();
+        assert_inconsistent(
+            parse_schedule(too_many.len() as u64, &too_many),
+            "between 1 and 255 releases",
+        );
+    }
+
+    #[test]
+    fn schedule_amounts_must_be_positive_and_sum_to_transfer() {
+        assert_inconsistent(
+            parse_schedule(1, &[(1000, 1), (2000, 0)]),
+            "must be positive",
+        );
+        assert_inconsistent(
+            parse_schedule(4, &[(1000, 1), (2000, 2)]),
+            "sum to the transferred amount",
+        );
+        assert_inconsistent(
+            parse_schedule(2, &[(1000, 1), (2000, 2)]),
+            "sum to the transferred amount",
+        );
+        // The sum of the releases overflows.
+        assert_inconsistent(
+            parse_schedule(u64::MAX, &[(1000, u64::MAX), (2000, 1)]),
+            "sum to the transferred amount",
+        );
+    }
+
+    #[test]
+    fn schedule_must_be_on_receiver_operation() {
+        let schedule = [(1000, 1)];
+        assert_inconsistent(
+            parse_transfer_with_schedule_transaction(
+                &schedule_operation(SENDER, -1, Some(&schedule)),
+                &schedule_operation(RECEIVER, 1, Some(&schedule)),
+            ),
+            "second operation",
+        );
+        assert!(matches!(
+            parse_transfer_with_schedule_transaction(
+                &schedule_operation(SENDER, -1, None),
+                &schedule_operation(RECEIVER, 1, None),
+            ),
+            Err(ApiError::RequiredFieldMissing(_))
+        ));
+    }
+}
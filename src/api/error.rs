@@ -1,3 +1,4 @@
+use crate::api::reject_reason::RejectReasonMetadata;
 use concordium_rust_sdk::{endpoints::RPCError, types::hashes::HashFromStrError};
 use thiserror::Error;
 
@@ -32,7 +33,7 @@ pub enum ApiError {
     // Invalid input: Unsupported field.
     #[error("field '{0}' is not supported")]
     UnsupportedFieldPresent(String),
-    #[error("sub-accounts are not supported")]
+    #[error("sub-accounts are not supported for this account")]
     SubAccountNotImplemented,
 
     // Invalid input: Missing field.
@@ -42,6 +43,8 @@ pub enum ApiError {
     // Invalid input: Invalid value or identifier (type or format).
     #[error("invalid account address '{0}'")]
     InvalidAccountAddress(String),
+    #[error("invalid sub-account address '{0}'")]
+    InvalidSubAccountAddress(String),
     #[error("invalid contract address '{0}'")]
     InvalidContractAddress(String),
     #[error("invalid currency")]
@@ -50,8 +53,6 @@ pub enum ApiError {
     InvalidTransactionIdentifier(String, HashFromStrError),
     #[error("invalid amount '{0}'")]
     InvalidAmount(String),
-    #[error("invalid block transaction request")]
-    InvalidBlockTransactionRequest,
     #[error("invalid block identifier")]
     InvalidBlockIdentifier(InvalidBlockIdentifierError),
     #[error("invalid signature '{0}': {1}")]
@@ -66,10 +67,18 @@ pub enum ApiError {
     InvalidConstructionOptions,
     #[error("invalid payloads metadata")]
     InvalidPayloadsMetadata,
+    #[error("invalid metadata of operation of type '{0}': {1}")]
+    InvalidOperationMetadata(String, String),
+    #[error("invalid value {1} of parameter '{0}'")]
+    InvalidParameter(String, i64),
+    #[error("invalid parameters of call method '{0}': {1}")]
+    InvalidCallParameters(String, String),
 
     // Invalid input: Unsupported field value.
     #[error("unsupported operation type '{0}'")]
     UnsupportedOperationType(String),
+    #[error("unsupported call method '{0}'")]
+    UnsupportedCallMethod(String),
 
     // Invalid input: Inconsistent value.
     #[error("inconsistent operations: {0}")]
@@ -84,11 +93,21 @@ pub enum ApiError {
     NoTransactionsMatched,
     #[error("no accounts matched")]
     NoAccountsMatched,
+    #[error("no contracts matched")]
+    NoContractsMatched,
+    #[error("no pools matched")]
+    NoPoolsMatched,
+    #[error("no tokens matched '{0}'")]
+    NoTokensMatched(String),
 
     // Identifier not resolved: Ambiguous identifier.
     #[error("multiple blocks matched")]
     MultipleBlocksMatched,
 
+    // Unavailable: Endpoint not enabled.
+    #[error("endpoint '{0}' is not enabled")]
+    EndpointNotEnabled(String),
+
     // Internal errors.
     #[error("unexpected internal error: {0}")]
     InternalServerError(anyhow::Error),
@@ -96,6 +115,10 @@ pub enum ApiError {
     // Proxy errors.
     #[error("client RPC error: {0}")]
     ClientRpcError(Box<RPCError>),
+    /// The node rejected the simulated execution of a transaction (the reason
+    /// is only present if it's known to this version of the server).
+    #[error("transaction rejected by the node")]
+    ContractInvocationRejected(Option<Box<RejectReasonMetadata>>),
 }
 
 impl From<RPCError> for ApiError {
@@ -0,0 +1,57 @@
+use crate::{
+    NetworkValidator,
+    api::{
+        error::{ApiError, ApiResult},
+        index::BlockEventLog,
+    },
+};
+use rosetta::models::*;
+
+/// Number of events returned if the request doesn't specify a limit.
+const DEFAULT_LIMIT: i64 = 100;
+/// Maximum number of events returned in a single response.
+const MAX_LIMIT: i64 = 1000;
+
+#[derive(Clone)]
+pub struct EventsApi {
+    network_validator: NetworkValidator,
+    log: Option<BlockEventLog>,
+}
+
+impl EventsApi {
+    pub fn new(network_validator: NetworkValidator, log: Option<BlockEventLog>) -> Self {
+        Self {
+            network_validator,
+            log,
+        }
+    }
+
+    pub async fn events_blocks(&self, req: EventsBlocksRequest) -> ApiResult<EventsBlocksResponse> {
+        self.network_validator
+            .validate_network_identifier(*req.network_identifier)?;
+        let log = match &self.log {
+            None => return Err(ApiError::EndpointNotEnabled("events/blocks".to_string())),
+            Some(l) => l,
+        };
+        let limit = match req.limit {
+            None => DEFAULT_LIMIT,
+            Some(l) if l > 0 => l.min(MAX_LIMIT),
+            Some(l) => return Err(ApiError::InvalidParameter("limit".to_string(), l)),
+        };
+        let max_sequence = log.max_sequence().map_err(ApiError::InternalServerError)?;
+        let offset = match req.offset {
+            // Return the most recent events if no offset is provided.
+            None => max_sequence.map_or(0, |s| (s + 1).saturating_sub(limit as u64)),
+            Some(o) if o >= 0 => o as u64,
+            Some(o) => return Err(ApiError::InvalidParameter("offset".to_string(), o)),
+        };
+        let events = log
+            .events(offset, limit as u64)
+            .map_err(ApiError::InternalServerError)?;
+        Ok(EventsBlocksResponse::new(
+            // -1 tells that there are no events yet.
+            max_sequence.map_or(-1, |s| s as i64),
+            events,
+        ))
+    }
+}
@@ -0,0 +1,546 @@
+use crate::api::{
+    block::BlockApi,
+    query::{HeadMode, QueryHelper},
+    transaction::{OPERATION_STATUS_FAIL, TRANSACTION_HASH_TOKENOMICS},
+};
+use anyhow::Context;
+use concordium_rust_sdk::types::hashes::BlockHash;
+use log::{info, warn};
+use redb::{Database, MultimapTableDefinition, ReadableTable, TableDefinition};
+use rosetta::models::{
+    BlockEvent, BlockEventType, BlockIdentifier, BlockTransaction, Currency, Operator, Transaction,
+};
+use std::{cmp::min, collections::BTreeSet, iter::Peekable, path::Path, sync::Arc, time::Duration};
+
+/// Key of an indexed transaction: The height of the containing block and the
+/// position of the transaction within that block (the tokenomics transaction
+/// always being at position 0).
+type TransactionKey = (u64, u32);
+
+/// Keys of indexed transactions as read from the database.
+type TransactionKeys<'a> =
+    Box<dyn Iterator<Item = Result<TransactionKey, redb::StorageError>> + 'a>;
+
+/// Indexed transactions stored as JSON encoded 'BlockTransaction' objects.
+const TRANSACTIONS: TableDefinition<TransactionKey, &str> = TableDefinition::new("transactions");
+/// Search terms (see 'transaction_terms') mapped to the keys of the
+/// transactions that they match.
+const TERMS: MultimapTableDefinition<&str, TransactionKey> = MultimapTableDefinition::new("terms");
+/// Progress of the indexer.
+const STATE: TableDefinition<&str, u64> = TableDefinition::new("state");
+const STATE_NEXT_HEIGHT: &str = "next_height";
+/// Height up to which the blocks in the block event log are known to be
+/// finalized. Only blocks above this height may be removed from the log.
+const STATE_BLOCK_EVENTS_FINALIZED_HEIGHT: &str = "block_events_finalized_height";
+
+/// Block events stored as JSON encoded 'BlockEvent' objects keyed by their
+/// sequence number.
+const BLOCK_EVENTS: TableDefinition<u64, &str> = TableDefinition::new("block_events");
+
+/// Interval between polls for newly finalized blocks once the index/log has
+/// caught up. This is on the order of the block time.
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+/// Maximum number of blocks to write to the index/log in a single database
+/// transaction.
+const BATCH_SIZE: u64 = 100;
+
+/// Open (or create) the database backing the transaction index and block event
+/// log.
+pub fn open_database(path: impl AsRef<Path>) -> anyhow::Result<Arc<Database>> {
+    let db = Database::create(path).context("Cannot open index database.")?;
+    Ok(Arc::new(db))
+}
+
+/// Embedded on-disk index of the transactions of all finalized blocks.
+/// The index is populated by 'follow' and queried by the Indexer API.
+#[derive(Clone)]
+pub struct TransactionIndex {
+    db: Arc<Database>,
+}
+
+impl TransactionIndex {
+    pub fn new(db: Arc<Database>) -> anyhow::Result<Self> {
+        // Ensure that all tables exist such that they may be opened for reading.
+        let write_txn = db.begin_write()?;
+        write_txn.open_table(TRANSACTIONS)?;
+        write_txn.open_multimap_table(TERMS)?;
+        write_txn.open_table(STATE)?;
+        write_txn.commit()?;
+        Ok(Self { db })
+    }
+
+    /// Height of the next block to be indexed.
+    pub fn next_height(&self) -> anyhow::Result<u64> {
+        let read_txn = self.db.begin_read()?;
+        let state = read_txn.open_table(STATE)?;
+        Ok(state.get(STATE_NEXT_HEIGHT)?.map_or(0, |v| v.value()))
+    }
+
+    /// Look up the transactions matching the provided terms (combined using
+    /// the given operator) in blocks no higher than 'max_block'.
+    /// The transactions are ordered by decreasing block height and position.
+    /// Returns the requested page of transactions along with the total number
+    /// of matches. Only the transactions on the page are loaded; the remaining
+    /// matches are counted by their keys.
+    pub fn search(
+        &self,
+        terms: &[String],
+        operator: Operator,
+        max_block: Option<u64>,
+        offset: usize,
+        limit: usize,
+    ) -> anyhow::Result<(Vec<BlockTransaction>, usize)> {
+        let max_key = (max_block.unwrap_or(u64::MAX), u32::MAX);
+        let read_txn = self.db.begin_read()?;
+        let transactions = read_txn.open_table(TRANSACTIONS)?;
+        let terms_table = read_txn.open_multimap_table(TERMS)?;
+
+        let keys: TransactionKeys = match terms {
+            [] => Box::new(
+                transactions
+                    .range(..=max_key)?
+                    .rev()
+                    .map(|e| e.map(|(k, _)| k.value())),
+            ),
+            _ => {
+                let mut term_keys = Vec::with_capacity(terms.len());
+                for term in terms {
+                    let keys: TransactionKeys = Box::new(
+                        terms_table
+                            .get(term.as_str())?
+                            .rev()
+                            .map(|k| k.map(|k| k.value()))
+                            .skip_while(move |k| matches!(k, Ok(k) if *k > max_key)),
+                    );
+                    term_keys.push(keys);
+                }
+                match operator {
+                    _ if term_keys.len() == 1 => term_keys.pop().unwrap(),
+                    Operator::And => Box::new(Intersection { keys: term_keys }),
+                    Operator::Or => Box::new(Union {
+                        keys: term_keys.into_iter().map(Iterator::peekable).collect(),
+                    }),
+                }
+            }
+        };
+        let (keys, total) = page(keys, offset, limit)?;
+
+        let mut res = Vec::with_capacity(keys.len());
+        for key in keys {
+            let value = transactions
+                .get(key)?
+                .with_context(|| format!("indexed transaction {:?} is missing", key))?;
+            res.push(serde_json::from_str(value.value())?);
+        }
+        Ok((res, total))
+    }
+
+    /// Keep indexing blocks as they get finalized.
+    /// Errors are logged and the indexing is retried after a short delay.
+    pub async fn follow(self, block_api: BlockApi, query_helper: QueryHelper) {
+        loop {
+            if let Err(err) = self.index_finalized_blocks(&block_api, &query_helper).await {
+                warn!("Indexing of finalized blocks failed: {:#}", err);
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    }
+
+    async fn index_finalized_blocks(
+        &self,
+        block_api: &BlockApi,
+        query_helper: &QueryHelper,
+    ) -> anyhow::Result<()> {
+        let last_finalized_height = query_helper
+            .query_consensus_info()
+            .await?
+            .last_finalized_block_height
+            .height;
+        let mut next_height = self.next_height()?;
+        while next_height <= last_finalized_height {
+            let batch_end_height = min(last_finalized_height, next_height + BATCH_SIZE - 1);
+            let mut batch = vec![];
+            for height in next_height..=batch_end_height {
+                let block_hash = query_helper
+                    .query_block_hash_from_height(height as i64)
+                    .await?;
+                let transactions = block_api.block_transactions(block_hash).await?;
+                batch.push((
+                    BlockIdentifier::new(height as i64, block_hash.to_string()),
+                    transactions,
+                ));
+            }
+            self.insert_blocks(batch)?;
+            info!("Indexed blocks up to height {}.", batch_end_height);
+            next_height = batch_end_height + 1;
+        }
+        Ok(())
+    }
+
+    fn insert_blocks(
+        &self,
+        blocks: Vec<(BlockIdentifier, Vec<Transaction>)>,
+    ) -> anyhow::Result<()> {
+        let write_txn = self.db.begin_write()?;
+        {
+            let mut transactions = write_txn.open_table(TRANSACTIONS)?;
+            let mut terms = write_txn.open_multimap_table(TERMS)?;
+            let mut state = write_txn.open_table(STATE)?;
+            for (block_identifier, block_transactions) in blocks {
+                let height = block_identifier.index as u64;
+                for (i, transaction) in block_transactions.into_iter().enumerate() {
+                    let key = (height, i as u32);
+                    for term in transaction_terms(&transaction) {
+                        terms.insert(term.as_str(), key)?;
+                    }
+                    let value = serde_json::to_string(&BlockTransaction::new(
+                        block_identifier.clone(),
+                        transaction,
+                    ))?;
+                    transactions.insert(key, value.as_str())?;
+                }
+                state.insert(STATE_NEXT_HEIGHT, height + 1)?;
+            }
+        }
+        write_txn.commit()?;
+        Ok(())
+    }
+}
+
+/// Persistent log of blocks being added to (and removed from) the chain.
+/// Each event is assigned a sequence number that is one larger than that of
+/// the previous event such that clients may resume from any point in the log.
+/// The log is populated by 'follow' and queried by the Events API.
+#[derive(Clone)]
+pub struct BlockEventLog {
+    db: Arc<Database>,
+}
+
+impl BlockEventLog {
+    pub fn new(db: Arc<Database>) -> anyhow::Result<Self> {
+        // Ensure that the table exists such that it may be opened for reading.
+        let write_txn = db.begin_write()?;
+        write_txn.open_table(BLOCK_EVENTS)?;
+        write_txn.open_table(STATE)?;
+        write_txn.commit()?;
+        Ok(Self { db })
+    }
+
+    /// Sequence number of the most recent event, if any.
+    pub fn max_sequence(&self) -> anyhow::Result<Option<u64>> {
+        let read_txn = self.db.begin_read()?;
+        let events = read_txn.open_table(BLOCK_EVENTS)?;
+        Ok(events.last()?.map(|(k, _)| k.value()))
+    }
+
+    /// Look up up to 'limit' events starting from sequence number 'offset'.
+    pub fn events(&self, offset: u64, limit: u64) -> anyhow::Result<Vec<BlockEvent>> {
+        let read_txn = self.db.begin_read()?;
+        let events = read_txn.open_table(BLOCK_EVENTS)?;
+        let mut res = vec![];
+        for e in events.range(offset..offset.saturating_add(limit))? {
+            let (_, v) = e?;
+            res.push(serde_json::from_str(v.value())?);
+        }
+        Ok(res)
+    }
+
+    /// Keep the log in sync with the chain ending in the head block: Blocks are
+    /// added as they get finalized or, when following the best block, as they
+    /// become part of the best chain. In the latter case, blocks that are
+    /// abandoned in favor of another branch are removed again.
+    /// Errors are logged and the operation is retried after a short delay.
+    pub async fn follow(self, query_helper: QueryHelper) {
+        loop {
+            if let Err(err) = self.sync_blocks(&query_helper).await {
+                warn!("Updating the block event log failed: {:#}", err);
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    }
+
+    async fn sync_blocks(&self, query_helper: &QueryHelper) -> anyhow::Result<()> {
+        let consensus_info = query_helper.query_consensus_info().await?;
+        let last_finalized_height = consensus_info.last_finalized_block_height.height;
+        // Hashes of the blocks of the head's branch above the last finalized block (in order of
+        // height), resolved by walking back from the best block.
+        let mut branch = vec![];
+        if query_helper.head_mode == HeadMode::Best {
+            let mut block_hash = consensus_info.best_block;
+            for _ in last_finalized_height..consensus_info.best_block_height.height {
+                branch.push(block_hash);
+                block_hash = query_helper
+                    .query_block_info_by_hash(&block_hash)
+                    .await?
+                    .block_parent;
+            }
+            branch.reverse();
+        }
+        let head_height = last_finalized_height + branch.len() as u64;
+        let block_hash_at_height = async |height: u64| -> anyhow::Result<BlockHash> {
+            if height <= last_finalized_height {
+                Ok(query_helper
+                    .query_block_hash_from_height(height as i64)
+                    .await?)
+            } else {
+                Ok(branch[(height - last_finalized_height - 1) as usize])
+            }
+        };
+
+        // Remove the blocks that are no longer part of the chain, most recent first.
+        let mut removed_blocks = vec![];
+        for block in self.unfinalized_blocks()? {
+            let height = block.index as u64;
+            if height <= head_height
+                && block_hash_at_height(height).await?.to_string() == block.hash
+            {
+                break;
+            }
+            removed_blocks.push(block);
+        }
+        if !removed_blocks.is_empty() {
+            self.append(removed_blocks, BlockEventType::Removed, None)?;
+        }
+
+        // Add the blocks that are missing from the log.
+        let mut next_height = self.next_height()?;
+        while next_height <= head_height {
+            let batch_end_height = min(head_height, next_height + BATCH_SIZE - 1);
+            let mut batch = vec![];
+            for height in next_height..=batch_end_height {
+                let block_hash = block_hash_at_height(height).await?;
+                batch.push(BlockIdentifier::new(height as i64, block_hash.to_string()));
+            }
+            self.append(
+                batch,
+                BlockEventType::Added,
+                Some(min(last_finalized_height, batch_end_height)),
+            )?;
+            next_height = batch_end_height + 1;
+        }
+        Ok(())
+    }
+
+    /// The blocks of the log above the height up to which they are known to be
+    /// finalized and that haven't been removed again, most recent first.
+    fn unfinalized_blocks(&self) -> anyhow::Result<Vec<BlockIdentifier>> {
+        let read_txn = self.db.begin_read()?;
+        let state = read_txn.open_table(STATE)?;
+        // Logs written without this state only contain finalized blocks.
+        let Some(finalized_height) = state
+            .get(STATE_BLOCK_EVENTS_FINALIZED_HEIGHT)?
+            .map(|v| v.value())
+        else {
+            return Ok(vec![]);
+        };
+        let events = read_txn.open_table(BLOCK_EVENTS)?;
+        let mut removed_hashes = BTreeSet::new();
+        let mut res = vec![];
+        for e in events.iter()?.rev() {
+            let (_, v) = e?;
+            let event: BlockEvent = serde_json::from_str(v.value())?;
+            if event.block_identifier.index as u64 <= finalized_height {
+                break;
+            }
+            match event._type {
+                BlockEventType::Removed => {
+                    removed_hashes.insert(event.block_identifier.hash);
+                }
+                BlockEventType::Added => {
+                    if !removed_hashes.remove(&event.block_identifier.hash) {
+                        res.push(*event.block_identifier);
+                    }
+                }
+            }
+        }
+        Ok(res)
+    }
+
+    /// Height of the next block to be added to the log.
+    fn next_height(&self) -> anyhow::Result<u64> {
+        let read_txn = self.db.begin_read()?;
+        let events = read_txn.open_table(BLOCK_EVENTS)?;
+        match events.last()? {
+            None => Ok(0),
+            Some((_, v)) => {
+                let event: BlockEvent = serde_json::from_str(v.value())?;
+                let height = event.block_identifier.index as u64;
+                // Blocks are always removed from the top, so a removed block is replaced by the
+                // next block to be added.
+                match event._type {
+                    BlockEventType::Added => Ok(height + 1),
+                    BlockEventType::Removed => Ok(height),
+                }
+            }
+        }
+    }
+
+    /// Append events of the given type for the given blocks and, if provided,
+    /// update the height up to which the logged blocks are finalized.
+    fn append(
+        &self,
+        block_identifiers: Vec<BlockIdentifier>,
+        type_: BlockEventType,
+        finalized_height: Option<u64>,
+    ) -> anyhow::Result<()> {
+        let write_txn = self.db.begin_write()?;
+        if let Some(h) = finalized_height {
+            let mut state = write_txn.open_table(STATE)?;
+            state.insert(STATE_BLOCK_EVENTS_FINALIZED_HEIGHT, h)?;
+        }
+        {
+            let mut events = write_txn.open_table(BLOCK_EVENTS)?;
+            let first_sequence = events.last()?.map_or(0, |(k, _)| k.value() + 1);
+            for (sequence, block_identifier) in (first_sequence..).zip(block_identifiers) {
+                let value = serde_json::to_string(&BlockEvent::new(
+                    sequence as i64,
+                    block_identifier,
+                    type_,
+                ))?;
+                events.insert(sequence, value.as_str())?;
+            }
+        }
+        write_txn.commit()?;
+        Ok(())
+    }
+}
+
+/// Skip the first 'offset' keys and collect up to 'limit' of the following ones.
+/// Returns the collected keys along with the total number of keys.
+fn page(
+    keys: TransactionKeys,
+    offset: usize,
+    limit: usize,
+) -> anyhow::Result<(Vec<TransactionKey>, usize)> {
+    let mut res = vec![];
+    let mut total = 0;
+    for k in keys {
+        let k = k?;
+        if total >= offset && res.len() < limit {
+            res.push(k);
+        }
+        total += 1;
+    }
+    Ok((res, total))
+}
+
+/// Keys yielded by all of the given iterators, each of which yields its keys
+/// in decreasing order. The iterators are advanced in turn to the largest key
+/// not exceeding the current candidate, so only the keys down to the last match
+/// are read.
+struct Intersection<'a> {
+    keys: Vec<TransactionKeys<'a>>,
+}
+
+impl Iterator for Intersection<'_> {
+    type Item = Result<TransactionKey, redb::StorageError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut candidate: Option<TransactionKey> = None;
+        // Number of consecutive iterators that have yielded the candidate.
+        let mut matches = 0;
+        for i in (0..self.keys.len()).cycle() {
+            let key = loop {
+                match self.keys[i].next()? {
+                    Err(err) => return Some(Err(err)),
+                    Ok(k) if candidate.is_none_or(|c| k <= c) => break k,
+                    Ok(_) => {}
+                }
+            };
+            if candidate == Some(key) {
+                matches += 1;
+            } else {
+                candidate = Some(key);
+                matches = 1;
+            }
+            if matches == self.keys.len() {
+                return Some(Ok(key));
+            }
+        }
+        None
+    }
+}
+
+/// Keys yielded by any of the given iterators, each of which yields its keys
+/// in decreasing order. The keys are merged in decreasing order without
+/// duplicates.
+struct Union<'a> {
+    keys: Vec<Peekable<TransactionKeys<'a>>>,
+}
+
+impl Iterator for Union<'_> {
+    type Item = Result<TransactionKey, redb::StorageError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut max: Option<TransactionKey> = None;
+        for keys in self.keys.iter_mut() {
+            match keys.peek() {
+                Some(Err(_)) => return keys.next(),
+                Some(Ok(k)) if max.is_none_or(|m| *k > m) => max = Some(*k),
+                _ => {}
+            }
+        }
+        let max = max?;
+        for keys in self.keys.iter_mut() {
+            if matches!(keys.peek(), Some(Ok(k)) if *k == max) {
+                keys.next();
+            }
+        }
+        Some(Ok(max))
+    }
+}
+
+/// Search terms under which the transaction is indexed.
+/// A transaction is indexed under the hash of the transaction
+/// (except for the tokenomics pseudo-transaction)
+/// as well as the account, type, status, and currency of all its operations.
+/// It's also indexed by whether the transaction succeeded.
+fn transaction_terms(transaction: &Transaction) -> BTreeSet<String> {
+    let mut res = BTreeSet::new();
+    let hash = &transaction.transaction_identifier.hash;
+    if hash != TRANSACTION_HASH_TOKENOMICS {
+        res.insert(hash_term(hash));
+    }
+    let mut success = true;
+    for op in transaction.operations.iter() {
+        res.insert(type_term(&op._type));
+        if let Some(account) = &op.account {
+            res.insert(address_term(&account.address));
+        }
+        if let Some(status) = &op.status {
+            res.insert(status_term(status));
+            if status == OPERATION_STATUS_FAIL {
+                success = false;
+            }
+        }
+        if let Some(amount) = &op.amount {
+            res.insert(currency_term(&amount.currency));
+        }
+    }
+    res.insert(success_term(success));
+    res
+}
+
+pub fn hash_term(hash: &str) -> String {
+    format!("hash:{}", hash.to_lowercase())
+}
+
+pub fn address_term(address: &str) -> String {
+    format!("address:{}", address)
+}
+
+pub fn type_term(type_: &str) -> String {
+    format!("type:{}", type_)
+}
+
+pub fn status_term(status: &str) -> String {
+    format!("status:{}", status)
+}
+
+pub fn success_term(success: bool) -> String {
+    format!("success:{}", success)
+}
+
+pub fn currency_term(currency: &Currency) -> String {
+    format!("currency:{}:{}", currency.symbol, currency.decimals)
+}
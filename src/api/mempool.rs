@@ -0,0 +1,235 @@
+use crate::{
+    NetworkValidator,
+    api::{
+        cis2::Cis2Tokens,
+        construction::operations_from_transaction,
+        error::{ApiError, ApiResult},
+        query::QueryHelper,
+        transaction::{MemoMetadata, map_transaction},
+    },
+};
+use concordium_rust_sdk::{
+    common::{SerdeSerialize, types::TransactionTime},
+    endpoints::QueryError,
+    types::{
+        TransactionStatus,
+        hashes::TransactionHash,
+        transactions::{AccountTransaction, EncodedPayload, PayloadLike},
+    },
+};
+use log::warn;
+use rosetta::models::*;
+use std::{
+    collections::BTreeMap,
+    str::FromStr,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// Interval between polls for the status of the tracked transactions.
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Transactions known to have been received by the node but not yet finalized.
+/// The node doesn't expose the contents of its transaction pool, so the set
+/// only includes transactions submitted through this server and ones that have
+/// been looked up using '/mempool/transaction' while pending.
+#[derive(Clone, Default)]
+pub struct MempoolTracker {
+    entries: Arc<Mutex<BTreeMap<TransactionHash, MempoolEntry>>>,
+}
+
+#[derive(Clone)]
+struct MempoolEntry {
+    /// The transaction itself if it was submitted through this server.
+    transaction: Option<AccountTransaction<EncodedPayload>>,
+}
+
+impl MempoolEntry {
+    fn is_expired(&self, now: TransactionTime) -> bool {
+        self.transaction
+            .as_ref()
+            .is_some_and(|tx| tx.header.expiry < now)
+    }
+}
+
+#[derive(SerdeSerialize)]
+struct MempoolTransactionMetadata {
+    /// Status of the transaction: "received" or "committed".
+    status: &'static str,
+}
+
+impl MempoolTracker {
+    /// Start tracking a transaction that was submitted through this server.
+    pub fn insert_submitted(
+        &self,
+        hash: TransactionHash,
+        transaction: AccountTransaction<EncodedPayload>,
+    ) {
+        self.entries.lock().unwrap().insert(
+            hash,
+            MempoolEntry {
+                transaction: Some(transaction),
+            },
+        );
+    }
+
+    /// Start tracking a transaction that is known to be pending, unless it's
+    /// already tracked.
+    fn insert_known(&self, hash: TransactionHash) {
+        self.entries
+            .lock()
+            .unwrap()
+            .entry(hash)
+            .or_insert(MempoolEntry { transaction: None });
+    }
+
+    fn remove(&self, hash: &TransactionHash) {
+        self.entries.lock().unwrap().remove(hash);
+    }
+
+    fn get(&self, hash: &TransactionHash) -> Option<MempoolEntry> {
+        self.entries.lock().unwrap().get(hash).cloned()
+    }
+
+    fn hashes(&self) -> Vec<TransactionHash> {
+        self.entries.lock().unwrap().keys().copied().collect()
+    }
+
+    /// Keep polling the status of the tracked transactions and evict the ones
+    /// that have been finalized, have expired, or are no longer known to the
+    /// node.
+    pub async fn follow(self, query_helper: QueryHelper) {
+        loop {
+            let now = transaction_time_now();
+            for hash in self.hashes() {
+                match query_helper
+                    .client
+                    .clone()
+                    .get_block_item_status(&hash)
+                    .await
+                {
+                    Ok(TransactionStatus::Finalized(_)) | Err(QueryError::NotFound) => {
+                        self.remove(&hash)
+                    }
+                    Ok(TransactionStatus::Received) => {
+                        if self.get(&hash).is_some_and(|e| e.is_expired(now)) {
+                            self.remove(&hash)
+                        }
+                    }
+                    Ok(TransactionStatus::Committed(_)) => {}
+                    Err(err) => warn!("Cannot query status of transaction {}: {}", hash, err),
+                }
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    }
+}
+
+fn transaction_time_now() -> TransactionTime {
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    TransactionTime { seconds }
+}
+
+#[derive(Clone)]
+pub struct MempoolApi {
+    network_validator: NetworkValidator,
+    query_helper: QueryHelper,
+    tracker: MempoolTracker,
+    cis2_tokens: Cis2Tokens,
+}
+
+impl MempoolApi {
+    pub fn new(
+        network_validator: NetworkValidator,
+        query_helper: QueryHelper,
+        tracker: MempoolTracker,
+        cis2_tokens: Cis2Tokens,
+    ) -> Self {
+        Self {
+            network_validator,
+            query_helper,
+            tracker,
+            cis2_tokens,
+        }
+    }
+
+    pub async fn mempool(&self, req: NetworkRequest) -> ApiResult<MempoolResponse> {
+        self.network_validator
+            .validate_network_identifier(*req.network_identifier)?;
+        Ok(MempoolResponse::new(
+            self.tracker
+                .hashes()
+                .iter()
+                .map(|h| TransactionIdentifier::new(h.to_string()))
+                .collect(),
+        ))
+    }
+
+    pub async fn mempool_transaction(
+        &self,
+        req: MempoolTransactionRequest,
+    ) -> ApiResult<MempoolTransactionResponse> {
+        self.network_validator
+            .validate_network_identifier(*req.network_identifier)?;
+        let hash_string = req.transaction_identifier.hash;
+        let hash = TransactionHash::from_str(hash_string.as_str())
+            .map_err(|e| ApiError::InvalidTransactionIdentifier(hash_string.clone(), e))?;
+        let (transaction, status) = match self
+            .query_helper
+            .query_transaction_status(hash_string)
+            .await?
+        {
+            TransactionStatus::Finalized(_) => {
+                self.tracker.remove(&hash);
+                return Err(ApiError::NoTransactionsMatched);
+            }
+            TransactionStatus::Committed(outcomes) => {
+                self.tracker.insert_known(hash);
+                // The transaction may be included in multiple (non-finalized) blocks.
+                // The outcome is reported for an arbitrary one of them.
+                let summary = match outcomes.into_values().next() {
+                    None => return Err(ApiError::NoTransactionsMatched),
+                    Some(s) => s,
+                };
+                let mut transaction = map_transaction(summary, &self.cis2_tokens);
+                // The outcome isn't final, so the statuses are not reported.
+                for op in transaction.operations.iter_mut() {
+                    op.status = None;
+                }
+                (transaction, "committed")
+            }
+            TransactionStatus::Received => {
+                self.tracker.insert_known(hash);
+                (self.received_transaction(&hash), "received")
+            }
+        };
+        Ok(MempoolTransactionResponse {
+            transaction: Box::new(transaction),
+            metadata: Some(serde_json::to_value(MempoolTransactionMetadata { status }).unwrap()),
+        })
+    }
+
+    /// Best-effort construction of a transaction that has been received but not
+    /// yet executed: If the transaction was submitted through this server and
+    /// it is of a type supported by the Construction API, the operations are
+    /// derived from its payload. Otherwise, the transaction has no operations.
+    fn received_transaction(&self, hash: &TransactionHash) -> Transaction {
+        let operations_and_memo =
+            self.tracker
+                .get(hash)
+                .and_then(|e| e.transaction)
+                .and_then(|tx| {
+                    let payload = tx.payload.decode().ok()?;
+                    operations_from_transaction(&tx.header, &payload).ok()
+                });
+        let (operations, memo) = operations_and_memo.unwrap_or_default();
+        Transaction {
+            transaction_identifier: Box::new(TransactionIdentifier::new(hash.to_string())),
+            operations,
+            related_transactions: None,
+            metadata: memo.map(|m| serde_json::to_value(MemoMetadata { memo: Some(m) }).unwrap()),
+        }
+    }
+}
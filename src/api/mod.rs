@@ -1,8 +1,16 @@
 pub mod account;
 pub mod amount;
 pub mod block;
+pub mod cache;
+pub mod call;
+pub mod cis2;
 pub mod construction;
 pub mod error;
+pub mod events;
+pub mod index;
+pub mod mempool;
 pub mod network;
 pub mod query;
+pub mod reject_reason;
+pub mod search;
 pub mod transaction;
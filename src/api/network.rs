@@ -1,14 +1,33 @@
 use crate::{
     QueryHelper,
-    api::{error::ApiResult, transaction::*},
+    api::{
+        amount::ccd_currency, call::CALL_METHODS, error::ApiResult, query::HeadMode, transaction::*,
+    },
     handler_error,
     validate::network::NetworkValidator,
 };
+use concordium_rust_sdk::v2::{NodeConsensusStatus, NodeDetails};
 use rosetta::models::*;
 use serde_json::json;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use crate::version::*;
 
+const SYNC_STAGE_SYNCED: &str = "synced";
+const SYNC_STAGE_CATCHING_UP: &str = "catching_up";
+const SYNC_STAGE_CONSENSUS_NOT_RUNNING: &str = "consensus_not_running";
+const SYNC_STAGE_FINALIZATION_LAGGING: &str = "finalization_lagging";
+
+/// Maximum age of the head block for the node to be considered synced.
+/// Blocks are normally produced and finalized within seconds.
+const MAX_SYNCED_HEAD_AGE: Duration = Duration::from_secs(60);
+
+/// Maximum number of blocks by which the best block may exceed the last
+/// finalized block for the node to be considered synced. Blocks are normally
+/// finalized within a few blocks, so a larger lag means that finalization has
+/// stalled (at least as seen by this node).
+const MAX_SYNCED_FINALIZATION_LAG: u64 = 30;
+
 #[derive(Clone)]
 pub struct NetworkApi {
     validator: NetworkValidator,
@@ -88,6 +107,16 @@ impl NetworkApi {
                     OPERATION_TYPE_VALIDATOR_PRIMED_FOR_SUSPENSION.to_string(),
                     OPERATION_TYPE_VALIDATOR_SUSPENDED.to_string(),
                     OPERATION_TYPE_TOKEN_UPDATE.to_string(),
+                    OPERATION_TYPE_TOKEN_TRANSFER.to_string(),
+                    OPERATION_TYPE_TOKEN_MINT.to_string(),
+                    OPERATION_TYPE_TOKEN_BURN.to_string(),
+                    OPERATION_TYPE_TOKEN_CREATION.to_string(),
+                    OPERATION_TYPE_CONTRACT_INTERRUPTED.to_string(),
+                    OPERATION_TYPE_CONTRACT_RESUMED.to_string(),
+                    OPERATION_TYPE_CONTRACT_UPGRADED.to_string(),
+                    OPERATION_TYPE_CIS2_TRANSFER.to_string(),
+                    OPERATION_TYPE_CIS2_MINT.to_string(),
+                    OPERATION_TYPE_CIS2_BURN.to_string(),
                 ],
                 errors: vec![
                     handler_error::invalid_input_unsupported_field_error(None),
@@ -99,6 +128,7 @@ impl NetworkApi {
                     handler_error::invalid_input_inconsistent_value_error(None, None),
                     handler_error::identifier_not_resolved_no_matches_error(None),
                     handler_error::identifier_not_resolved_multiple_matches_error(None),
+                    handler_error::unavailable_endpoint_not_enabled_error(None),
                     handler_error::internal_server_error(),
                     handler_error::proxy_client_rpc_error(None),
                     handler_error::proxy_client_query_error(None),
@@ -106,8 +136,8 @@ impl NetworkApi {
                 historical_balance_lookup: true,
                 timestamp_start_index: None, /* not populated as the genesis block has a
                                               * valid time stamp */
-                call_methods: vec![], // Call API is not implemented
-                balance_exemptions: vec![],
+                call_methods: CALL_METHODS.iter().map(|m| m.to_string()).collect(),
+                balance_exemptions: balance_exemptions(),
                 mempool_coins: false,              // mempool is not available
                 block_hash_case: Some(Case::Null), // case insensitive
                 transaction_hash_case: Some(Case::Null), // case insensitive
@@ -119,6 +149,24 @@ impl NetworkApi {
         self.validator
             .validate_network_identifier(*req.network_identifier)?;
         let consensus_status = self.query_helper.query_consensus_info().await?;
+        let node_info = self.query_helper.client.clone().get_node_info().await?;
+        // Defining "current" block as the head block (by default the last finalized block).
+        let (current_block_hash, current_block_height) = match self.query_helper.head_mode {
+            HeadMode::LastFinalized => (
+                consensus_status.last_finalized_block,
+                consensus_status.last_finalized_block_height,
+            ),
+            HeadMode::Best => (
+                consensus_status.best_block,
+                consensus_status.best_block_height,
+            ),
+        };
+        let current_block_timestamp = self
+            .query_helper
+            .query_block_info_by_hash(&current_block_hash)
+            .await?
+            .block_slot_time
+            .timestamp_millis();
         let peer_list = self
             .query_helper
             .client
@@ -127,25 +175,24 @@ impl NetworkApi {
             .await?
             .peers;
         Ok(NetworkStatusResponse {
-            // Defining "current" block as last finalized block.
             current_block_identifier: Box::new(BlockIdentifier {
-                index: consensus_status.last_finalized_block_height.height as i64,
-                hash: consensus_status.last_finalized_block.to_string(),
+                index: current_block_height.height as i64,
+                hash: current_block_hash.to_string(),
             }),
-            current_block_timestamp: self
-                .query_helper
-                .query_block_info_by_hash(&consensus_status.last_finalized_block)
-                .await?
-                .block_slot_time
-                .timestamp_millis(),
+            current_block_timestamp,
             genesis_block_identifier: Box::new(BlockIdentifier {
                 index: 0,
                 hash: consensus_status.genesis_block.to_string(),
             }),
             oldest_block_identifier: None, /* not relevant as the implementation doesn't prune
                                             * blocks */
-            sync_status: None, /* the connected node's sync status is not easily
-                                * available and thus currently not exposed here */
+            sync_status: Some(Box::new(sync_status(
+                current_block_height.height,
+                consensus_status.last_finalized_block_height.height,
+                consensus_status.best_block_height.height,
+                current_block_timestamp,
+                &node_info.details,
+            ))),
             peers: Some(
                 peer_list
                     .iter()
@@ -158,3 +205,71 @@ impl NetworkApi {
         })
     }
 }
+
+/// Derive the sync status from the current (head), last finalized, and best
+/// block of the node: The node doesn't expose whether it's catching up, so it's
+/// considered synced if consensus is running, the current block is recent, and
+/// finalization keeps up with the best block.
+///
+/// With `--head best`, the head is the best block, so `current_index` and
+/// `target_index` are always equal and only the age of the head block and the
+/// finalization lag tell whether the node is synced.
+fn sync_status(
+    current_height: u64,
+    last_finalized_height: u64,
+    best_block_height: u64,
+    current_timestamp_millis: i64,
+    node_details: &NodeDetails,
+) -> SyncStatus {
+    let now_millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis() as i64);
+    let head_age_millis = now_millis - current_timestamp_millis;
+    let consensus_running = !matches!(
+        node_details,
+        NodeDetails::Bootstrapper | NodeDetails::Node(NodeConsensusStatus::ConsensusNotRunning)
+    );
+    let stage = if !consensus_running {
+        SYNC_STAGE_CONSENSUS_NOT_RUNNING
+    } else if head_age_millis > MAX_SYNCED_HEAD_AGE.as_millis() as i64 {
+        SYNC_STAGE_CATCHING_UP
+    } else if best_block_height.saturating_sub(last_finalized_height) > MAX_SYNCED_FINALIZATION_LAG
+    {
+        SYNC_STAGE_FINALIZATION_LAGGING
+    } else {
+        SYNC_STAGE_SYNCED
+    };
+    SyncStatus {
+        current_index: Some(current_height as i64),
+        target_index: Some(best_block_height.max(current_height) as i64),
+        stage: Some(stage.to_string()),
+        synced: Some(stage == SYNC_STAGE_SYNCED),
+    }
+}
+
+/// The balances of the sub-accounts change without corresponding operations
+/// when scheduled releases unlock, pending stake reductions take effect,
+/// cooldowns expire, and rewards are restaked.
+/// The balance of the account itself is not affected by any of these.
+fn balance_exemptions() -> Vec<BalanceExemption> {
+    let ccd = Box::new(ccd_currency());
+    let exemption = |sub_account: &str, exemption_type: ExemptionType| BalanceExemption {
+        sub_account_address: Some(sub_account.to_string()),
+        currency: Some(ccd.clone()),
+        exemption_type: Some(exemption_type),
+    };
+    vec![
+        // Restaked rewards increase the stake, and pending reductions decrease it
+        // when they take effect (moving the amount into cooldown).
+        exemption(SUB_ACCOUNT_STAKED, ExemptionType::Dynamic),
+        // Stake reductions taking effect increase the cooldown, and expiring
+        // cooldowns decrease it.
+        exemption(SUB_ACCOUNT_COOLDOWN, ExemptionType::Dynamic),
+        // Releases unlock (decrease) and stake or cooldown covering scheduled
+        // amounts changes (either way).
+        exemption(SUB_ACCOUNT_LOCKED, ExemptionType::Dynamic),
+        // Releases unlock and cooldowns expire (increase), and stake or cooldown
+        // covering more of the balance, e.g. on stake increases, decreases it.
+        exemption(SUB_ACCOUNT_AVAILABLE, ExemptionType::Dynamic),
+    ]
+}
@@ -1,15 +1,20 @@
 use crate::api::{
+    cache::BlockCache,
     error::{ApiError, ApiResult, InvalidBlockIdentifierError},
     transaction::*,
 };
 use concordium_rust_sdk::{
+    cis2,
     common::{types::Amount, upward::Upward},
     endpoints::{BlocksAtHeightInput, QueryError},
     id::types::AccountAddress,
+    protocol_level_tokens::{AccountToken, TokenId, TokenInfo},
+    smart_contracts::common::{OwnedParameter, OwnedReceiveName, from_bytes, to_bytes},
     types::{
+        Address as ChainAddress,
         hashes::{BlockHash, TransactionHash},
         queries::{BlockInfo, ConsensusInfo},
-        smart_contracts::InstanceInfo,
+        smart_contracts::{ContractContext, InstanceInfo, InvokeContractResult},
         *,
     },
     v2::{self, Client, IntoBlockIdentifier, RPCError},
@@ -18,24 +23,70 @@ use futures::{Stream, TryStreamExt};
 use rosetta::models::{AccountIdentifier, PartialBlockIdentifier};
 use std::str::FromStr;
 
+/// The block that is considered the current head of the chain.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum HeadMode {
+    /// The head is the last finalized block.
+    LastFinalized,
+    /// The head is the best block, which may not yet be finalized.
+    Best,
+}
+
+impl FromStr for HeadMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "finalized" => Ok(Self::LastFinalized),
+            "best" => Ok(Self::Best),
+            _ => Err(anyhow::anyhow!(
+                "invalid head mode '{}' (expected 'finalized' or 'best')",
+                s
+            )),
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct QueryHelper {
     pub client: Client,
+    pub head_mode: HeadMode,
+    /// Cache of data derived from finalized blocks.
+    pub cache: BlockCache,
 }
 
 impl QueryHelper {
-    pub fn new(client: Client) -> Self {
-        Self { client }
+    pub fn new(client: Client, head_mode: HeadMode, cache: BlockCache) -> Self {
+        Self {
+            client,
+            head_mode,
+            cache,
+        }
+    }
+
+    /// Identifier of the block that is considered the current head of the chain.
+    pub fn head_block_identifier(&self) -> v2::BlockIdentifier {
+        match self.head_mode {
+            HeadMode::LastFinalized => v2::BlockIdentifier::LastFinal,
+            HeadMode::Best => v2::BlockIdentifier::Best,
+        }
     }
 
     pub async fn query_account_balance(
         &self,
         block_identifier: Option<Box<PartialBlockIdentifier>>,
         account_identifier: &AccountIdentifier,
-    ) -> ApiResult<(BlockInfo, Amount)> {
+    ) -> ApiResult<(BlockInfo, Amount, Vec<AccountToken>)> {
+        let address = account_address_from_string(&account_identifier.address)?;
+        let sub_account = sub_account_from_identifier(account_identifier)?;
+        // Sub-accounts are only supported for ordinary accounts.
+        if sub_account.is_some() && !matches!(address, Address::Account(_)) {
+            return Err(ApiError::SubAccountNotImplemented);
+        }
         let block_info = self.query_block_info(block_identifier).await?;
         let block_hash = block_info.block_hash;
-        let address = account_address_from_identifier(account_identifier)?;
+        // Only ordinary accounts may hold protocol-level tokens.
+        let mut tokens = vec![];
         let amount = match address {
             Address::Account(addr) => {
                 let acc_id = v2::AccountIdentifier::Address(addr);
@@ -45,7 +96,13 @@ impl QueryHelper {
                     .get_account_info(&acc_id, &block_hash)
                     .await
                 {
-                    Ok(i) => i.response.account_amount,
+                    Ok(i) => match sub_account {
+                        None => {
+                            tokens = i.response.tokens;
+                            i.response.account_amount
+                        }
+                        Some(s) => sub_account_amount(&i.response, s),
+                    },
                     Err(err) => handle_query_error(err)?,
                 }
             }
@@ -73,6 +130,10 @@ impl QueryHelper {
                     RewardsOverview::V1 { common, .. } => common.finalization_reward_account,
                 }
             }
+            Address::GasAccount => match self.query_tokenomics_info(&block_hash).await? {
+                RewardsOverview::V0 { data } => data.gas_account,
+                RewardsOverview::V1 { common, .. } => common.gas_account,
+            },
             Address::FoundationAccrueAccount => {
                 match self.query_tokenomics_info(&block_hash).await? {
                     RewardsOverview::V0 { .. } => {
@@ -105,7 +166,7 @@ impl QueryHelper {
                 },
             },
         };
-        Ok((block_info, amount))
+        Ok((block_info, amount, tokens))
     }
 
     pub async fn query_consensus_info(&self) -> ApiResult<ConsensusInfo> {
@@ -142,14 +203,24 @@ impl QueryHelper {
         &self,
         block_id: impl IntoBlockIdentifier,
     ) -> ApiResult<BlockInfo> {
-        map_query_result(
+        let block_id = block_id.into_block_identifier();
+        if let v2::BlockIdentifier::Given(block_hash) = block_id {
+            if let Some(block_info) = self.cache.block_info(&block_hash) {
+                return Ok(block_info);
+            }
+        }
+        let block_info = map_query_result(
             self.client
                 .clone()
                 .get_block_info(block_id)
                 .await
                 .map(|x| x.response),
             ApiError::NoBlocksMatched,
-        )
+        )?;
+        if block_info.finalized {
+            self.cache.insert_block_info(block_info.clone());
+        }
+        Ok(block_info)
     }
 
     pub async fn query_block_item_summary(
@@ -196,12 +267,100 @@ impl QueryHelper {
         )
     }
 
+    pub async fn query_token_info(
+        &self,
+        token_id: TokenId,
+        block_id: impl IntoBlockIdentifier,
+    ) -> ApiResult<TokenInfo> {
+        let token_id_string = token_id.to_string();
+        map_query_result(
+            self.client
+                .clone()
+                .get_token_info(token_id, block_id)
+                .await
+                .map(|x| x.response),
+            ApiError::NoTokensMatched(token_id_string),
+        )
+    }
+
+    /// Balance of a CIS-2 token as of the given block, obtained by invoking the
+    /// 'balanceOf' entrypoint of the token contract. 'currency' is the symbol of
+    /// the requested currency for error reporting.
+    pub async fn query_cis2_balance(
+        &self,
+        contract: ContractAddress,
+        token_id: cis2::TokenId,
+        owner: ChainAddress,
+        block_hash: &BlockHash,
+        currency: &str,
+    ) -> ApiResult<cis2::TokenAmount> {
+        let no_tokens_matched = || ApiError::NoTokensMatched(currency.to_string());
+        let instance = map_query_result(
+            self.client
+                .clone()
+                .get_instance_info(contract, block_hash)
+                .await
+                .map(|x| x.response),
+            ApiError::NoContractsMatched,
+        )?;
+        let contract_name = match &instance {
+            InstanceInfo::V0 { name, .. } => name,
+            InstanceInfo::V1 { name, .. } => name,
+        }
+        .as_contract_name()
+        .contract_name();
+        let method = OwnedReceiveName::new(format!("{}.balanceOf", contract_name))
+            .map_err(|_| no_tokens_matched())?;
+        let params = cis2::BalanceOfQueryParams {
+            queries: vec![cis2::BalanceOfQuery {
+                token_id,
+                address: owner,
+            }],
+        };
+        let parameter =
+            OwnedParameter::try_from(to_bytes(&params)).map_err(|_| no_tokens_matched())?;
+        let context = ContractContext {
+            invoker: None,
+            contract,
+            amount: Amount::from_micro_ccd(0),
+            method,
+            parameter,
+            energy: None,
+        };
+        let result = map_query_result(
+            self.client
+                .clone()
+                .invoke_instance(block_hash, &context)
+                .await
+                .map(|x| x.response),
+            ApiError::NoContractsMatched,
+        )?;
+        // The contract rejecting the query or returning something that isn't a
+        // CIS-2 balance means that it doesn't have the token.
+        let return_value = match result {
+            InvokeContractResult::Success {
+                return_value: Some(v),
+                ..
+            } => v.value,
+            _ => return Err(no_tokens_matched()),
+        };
+        let cis2::BalanceOfQueryResponse(mut amounts) =
+            from_bytes(&return_value).map_err(|_| no_tokens_matched())?;
+        match amounts.pop() {
+            Some(amount) if amounts.is_empty() => Ok(amount),
+            _ => Err(no_tokens_matched()),
+        }
+    }
+
     pub async fn query_block_hash_from_height(&self, height: i64) -> ApiResult<BlockHash> {
         if height < 0 {
             return Err(ApiError::InvalidBlockIdentifier(
                 InvalidBlockIdentifierError::InvalidIndex(height),
             ));
         };
+        if let Some(block_hash) = self.cache.block_hash(height as u64) {
+            return Ok(block_hash);
+        }
         let block_height = BlocksAtHeightInput::Absolute {
             height: AbsoluteBlockHeight {
                 height: height as u64,
@@ -221,6 +380,32 @@ impl QueryHelper {
         }
     }
 
+    /// Resolve the hash of the block at the given height on the chain ending in
+    /// the head block. Above the last finalized block, this is done by walking
+    /// back from the best block as the node may know several blocks at the same
+    /// height.
+    pub async fn query_head_block_hash_from_height(&self, height: i64) -> ApiResult<BlockHash> {
+        if self.head_mode == HeadMode::LastFinalized || height < 0 {
+            return self.query_block_hash_from_height(height).await;
+        }
+        let consensus_info = self.query_consensus_info().await?;
+        let height = height as u64;
+        if height <= consensus_info.last_finalized_block_height.height {
+            return self.query_block_hash_from_height(height as i64).await;
+        }
+        if height > consensus_info.best_block_height.height {
+            return Err(ApiError::NoBlocksMatched);
+        }
+        let mut block_hash = consensus_info.best_block;
+        for _ in height..consensus_info.best_block_height.height {
+            block_hash = self
+                .query_block_info_by_hash(&block_hash)
+                .await?
+                .block_parent;
+        }
+        Ok(block_hash)
+    }
+
     pub async fn query_transaction_status(
         &self,
         hash_string: String,
@@ -233,18 +418,33 @@ impl QueryHelper {
         )
     }
 
+    /// Resolve a partial block identifier into the info of the block.
+    /// The heights of finalized blocks resolved by index are cached.
     pub async fn query_block_info(
         &self,
         block_id: Option<Box<PartialBlockIdentifier>>,
+    ) -> ApiResult<BlockInfo> {
+        let resolved_by_index = block_id.as_ref().is_some_and(|bid| bid.index.is_some());
+        let block_info = self.query_partial_block_info(block_id).await?;
+        if resolved_by_index && block_info.finalized {
+            self.cache
+                .insert_block_hash(block_info.block_height.height, block_info.block_hash);
+        }
+        Ok(block_info)
+    }
+
+    async fn query_partial_block_info(
+        &self,
+        block_id: Option<Box<PartialBlockIdentifier>>,
     ) -> ApiResult<BlockInfo> {
         match block_id {
             None => {
-                self.query_block_info_by_hash(v2::BlockIdentifier::LastFinal)
+                self.query_block_info_by_hash(self.head_block_identifier())
                     .await
             }
             Some(bid) => match (bid.index, bid.hash) {
                 (Some(height), None) => {
-                    let block_hash = self.query_block_hash_from_height(height).await?;
+                    let block_hash = self.query_head_block_hash_from_height(height).await?;
                     self.query_block_info_by_hash(block_hash).await
                 }
                 (None, Some(hash)) => {
@@ -253,7 +453,7 @@ impl QueryHelper {
                 }
                 (Some(height), Some(hash)) => {
                     let block_hash_string = block_hash_from_string(hash.as_str())?;
-                    let block_hash_height = self.query_block_hash_from_height(height).await?;
+                    let block_hash_height = self.query_head_block_hash_from_height(height).await?;
 
                     if block_hash_string == block_hash_height {
                         self.query_block_info_by_hash(block_hash_string).await
@@ -310,6 +510,8 @@ pub enum Address {
     BakingRewardAccount,
     /// Virtual finalization reward account.
     FinalizationRewardAccount,
+    /// Virtual GAS account.
+    GasAccount,
     /// Virtual foundation accrue account.
     FoundationAccrueAccount,
     /// Virtual pool accrue account. Baker ID of None denotes the accrue account
@@ -317,6 +519,58 @@ pub enum Address {
     PoolAccrueAccount(Option<BakerId>),
 }
 
+/// Sub-accounts that partition the CCD balance of an ordinary account.
+#[derive(Clone, Copy)]
+pub enum SubAccount {
+    /// Amount staked as validator or delegator.
+    Staked,
+    /// Amount in cooldown after having been unstaked.
+    Cooldown,
+    /// Amount locked by the release schedule that isn't staked or in cooldown.
+    Locked,
+    /// Amount available for transfers.
+    Available,
+}
+
+pub fn sub_account_from_identifier(id: &AccountIdentifier) -> ApiResult<Option<SubAccount>> {
+    match id.sub_account.as_deref() {
+        None => Ok(None),
+        Some(s) => match s.address.as_str() {
+            SUB_ACCOUNT_STAKED => Ok(Some(SubAccount::Staked)),
+            SUB_ACCOUNT_COOLDOWN => Ok(Some(SubAccount::Cooldown)),
+            SUB_ACCOUNT_LOCKED => Ok(Some(SubAccount::Locked)),
+            SUB_ACCOUNT_AVAILABLE => Ok(Some(SubAccount::Available)),
+            _ => Err(ApiError::InvalidSubAccountAddress(s.address.clone())),
+        },
+    }
+}
+
+/// Compute the amount of the given sub-account such that the amounts of all
+/// sub-accounts sum to the total balance of the account:
+/// Stake and cooldown may be locked by the release schedule, so only the
+/// remaining part of the scheduled amount is attributed to 'locked'.
+fn sub_account_amount(info: &AccountInfo, sub_account: SubAccount) -> Amount {
+    let total = info.account_amount.micro_ccd();
+    let staked = match &info.account_stake {
+        None => 0,
+        Some(AccountStakingInfo::Baker { staked_amount, .. }) => staked_amount.micro_ccd(),
+        Some(AccountStakingInfo::Delegated { staked_amount, .. }) => staked_amount.micro_ccd(),
+    };
+    let cooldown: u64 = info.cooldowns.iter().map(|c| c.amount.micro_ccd()).sum();
+    let locked = info
+        .account_release_schedule
+        .total
+        .micro_ccd()
+        .saturating_sub(staked + cooldown);
+    let amount = match sub_account {
+        SubAccount::Staked => staked,
+        SubAccount::Cooldown => cooldown,
+        SubAccount::Locked => locked,
+        SubAccount::Available => total.saturating_sub(staked + cooldown + locked),
+    };
+    Amount::from_micro_ccd(amount)
+}
+
 pub fn account_address_from_identifier(id: &AccountIdentifier) -> ApiResult<Address> {
     match id.sub_account {
         None => account_address_from_string(&id.address),
@@ -329,6 +583,7 @@ pub fn account_address_from_string(addr: &str) -> ApiResult<Address> {
         ACCOUNT_REWARD_BAKING => Ok(Address::BakingRewardAccount),
         ACCOUNT_REWARD_FINALIZATION => Ok(Address::FinalizationRewardAccount),
         ACCOUNT_ACCRUE_FOUNDATION => Ok(Address::FoundationAccrueAccount),
+        ACCOUNT_GAS => Ok(Address::GasAccount),
         _ => {
             if let Some(pool) = addr.strip_prefix(ACCOUNT_ACCRUE_POOL_PREFIX) {
                 if pool == POOL_PASSIVE {
@@ -0,0 +1,444 @@
+use crate::api::transaction::contract_address_string;
+use concordium_rust_sdk::{
+    common::{SerdeSerialize, to_bytes, types::Amount},
+    id::types::AccountAddress,
+    types::{Address, RejectReason},
+};
+
+/// Version of the schema of [`RejectReasonMetadata`].
+/// Must be incremented on any incompatible change to the schema
+/// (i.e. removal or change of the meaning of a reason code or field).
+pub const REJECT_REASON_SCHEMA_VERSION: u32 = 1;
+
+/// Explicit representation of the reason for a transaction being rejected.
+/// The structure is independent of the serialization of [`RejectReason`] in
+/// the SDK such that it only changes when the schema version is bumped.
+#[derive(SerdeSerialize, Debug)]
+pub struct RejectReasonMetadata {
+    schema_version: u32,
+    /// Stable identifier of the reason in snake_case.
+    reason_code: &'static str,
+    /// Human readable description of the reason.
+    message: String,
+    #[serde(flatten)]
+    fields: RejectReasonFields,
+}
+
+/// Fields describing the details of a reject reason.
+/// Each reason code only includes the fields that are relevant for it.
+/// All fields are plain strings and numbers formatted by this module (rather
+/// than SDK types) such that their format is covered by the schema version.
+#[derive(SerdeSerialize, Default, Debug)]
+struct RejectReasonFields {
+    /// Account or contract address formatted as in account identifiers.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    address: Option<String>,
+    /// Amount in µCCD.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    amount: Option<String>,
+    /// Hex encoded module reference.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    module_ref: Option<String>,
+    /// Contract address formatted as in account identifiers.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    contract_address: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    init_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    receive_name: Option<String>,
+    /// Hex encoded parameter of a rejected contract invocation.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    parameter: Option<String>,
+    /// Error code returned by the contract.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    contract_reject_code: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    baker_id: Option<u64>,
+    /// Hex encoded aggregation verification key of a validator.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    aggregation_key: Option<String>,
+    /// Hex encoded credential registration IDs.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    credential_ids: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    token_id: Option<String>,
+    /// Type of the reject reason reported by the token module.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    token_reason_type: Option<String>,
+    /// Hex encoded CBOR details of the reject reason reported by the token
+    /// module.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    token_reason_details: Option<String>,
+}
+
+fn reason(
+    reason_code: &'static str,
+    message: impl Into<String>,
+    fields: RejectReasonFields,
+) -> RejectReasonMetadata {
+    RejectReasonMetadata {
+        schema_version: REJECT_REASON_SCHEMA_VERSION,
+        reason_code,
+        message: message.into(),
+        fields,
+    }
+}
+
+fn simple_reason(reason_code: &'static str, message: &str) -> RejectReasonMetadata {
+    reason(reason_code, message, RejectReasonFields::default())
+}
+
+fn address_string(address: &Address) -> String {
+    match address {
+        Address::Account(a) => a.to_string(),
+        Address::Contract(a) => contract_address_string(a),
+    }
+}
+
+fn account_fields(address: &AccountAddress) -> RejectReasonFields {
+    RejectReasonFields {
+        address: Some(address.to_string()),
+        ..Default::default()
+    }
+}
+
+fn amount_string(amount: &Amount) -> String {
+    amount.micro_ccd().to_string()
+}
+
+pub fn reject_reason_metadata(reject_reason: &RejectReason) -> RejectReasonMetadata {
+    match reject_reason {
+        RejectReason::ModuleNotWF => simple_reason(
+            "module_not_well_formed",
+            "smart contract module failed validation",
+        ),
+        RejectReason::ModuleHashAlreadyExists { contents } => reason(
+            "module_hash_already_exists",
+            format!("module with reference {} already exists", contents),
+            RejectReasonFields {
+                module_ref: Some(contents.to_string()),
+                ..Default::default()
+            },
+        ),
+        RejectReason::InvalidAccountReference { contents } => reason(
+            "invalid_account_reference",
+            format!("account {} does not exist", contents),
+            account_fields(contents),
+        ),
+        RejectReason::InvalidInitMethod {
+            contents: (module_ref, init_name),
+        } => reason(
+            "invalid_init_method",
+            format!("module {} has no init function '{}'", module_ref, init_name),
+            RejectReasonFields {
+                module_ref: Some(module_ref.to_string()),
+                init_name: Some(init_name.to_string()),
+                ..Default::default()
+            },
+        ),
+        RejectReason::InvalidReceiveMethod {
+            contents: (module_ref, receive_name),
+        } => reason(
+            "invalid_receive_method",
+            format!(
+                "module {} has no receive function '{}'",
+                module_ref, receive_name
+            ),
+            RejectReasonFields {
+                module_ref: Some(module_ref.to_string()),
+                receive_name: Some(receive_name.to_string()),
+                ..Default::default()
+            },
+        ),
+        RejectReason::InvalidModuleReference { contents } => reason(
+            "invalid_module_reference",
+            format!("module with reference {} does not exist", contents),
+            RejectReasonFields {
+                module_ref: Some(contents.to_string()),
+                ..Default::default()
+            },
+        ),
+        RejectReason::InvalidContractAddress { contents } => reason(
+            "invalid_contract_address",
+            format!(
+                "contract {} does not exist",
+                contract_address_string(contents)
+            ),
+            RejectReasonFields {
+                contract_address: Some(contract_address_string(contents)),
+                ..Default::default()
+            },
+        ),
+        RejectReason::RuntimeFailure => simple_reason(
+            "runtime_failure",
+            "smart contract execution failed at runtime",
+        ),
+        RejectReason::AmountTooLarge {
+            contents: (address, amount),
+        } => reason(
+            "amount_too_large",
+            format!(
+                "amount of {} µCCD exceeds the balance of {}",
+                amount_string(amount),
+                address_string(address)
+            ),
+            RejectReasonFields {
+                address: Some(address_string(address)),
+                amount: Some(amount_string(amount)),
+                ..Default::default()
+            },
+        ),
+        RejectReason::SerializationFailure => simple_reason(
+            "serialization_failure",
+            "transaction payload could not be deserialized",
+        ),
+        RejectReason::OutOfEnergy => {
+            simple_reason("out_of_energy", "transaction ran out of energy")
+        }
+        RejectReason::RejectedInit { reject_reason } => reason(
+            "rejected_init",
+            format!(
+                "contract initialization rejected with code {}",
+                reject_reason
+            ),
+            RejectReasonFields {
+                contract_reject_code: Some(*reject_reason),
+                ..Default::default()
+            },
+        ),
+        RejectReason::RejectedReceive {
+            reject_reason,
+            contract_address,
+            receive_name,
+            parameter,
+        } => reason(
+            "rejected_receive",
+            format!(
+                "invocation of '{}' on contract {} rejected with code {}",
+                receive_name,
+                contract_address_string(contract_address),
+                reject_reason
+            ),
+            RejectReasonFields {
+                contract_address: Some(contract_address_string(contract_address)),
+                receive_name: Some(receive_name.to_string()),
+                parameter: Some(hex::encode(parameter.as_ref())),
+                contract_reject_code: Some(*reject_reason),
+                ..Default::default()
+            },
+        ),
+        RejectReason::InvalidProof => simple_reason(
+            "invalid_proof",
+            "proof that the baker owns the keys is invalid",
+        ),
+        RejectReason::AlreadyABaker { contents } => reason(
+            "already_a_baker",
+            format!("account is already registered as baker {}", contents),
+            RejectReasonFields {
+                baker_id: Some(contents.id.index),
+                ..Default::default()
+            },
+        ),
+        RejectReason::NotABaker { contents } => reason(
+            "not_a_baker",
+            format!("account {} is not a baker", contents),
+            account_fields(contents),
+        ),
+        RejectReason::InsufficientBalanceForBakerStake => simple_reason(
+            "insufficient_balance_for_baker_stake",
+            "balance of the account is insufficient for the baker stake",
+        ),
+        RejectReason::StakeUnderMinimumThresholdForBaking => simple_reason(
+            "stake_under_minimum_threshold_for_baking",
+            "baker stake is below the minimum threshold",
+        ),
+        RejectReason::BakerInCooldown => simple_reason(
+            "baker_in_cooldown",
+            "baker stake cannot be changed while a change is in cooldown",
+        ),
+        RejectReason::DuplicateAggregationKey { contents } => reason(
+            "duplicate_aggregation_key",
+            "aggregation key is already in use by another baker",
+            RejectReasonFields {
+                aggregation_key: Some(hex::encode(to_bytes(&**contents))),
+                ..Default::default()
+            },
+        ),
+        RejectReason::NonExistentCredentialID => simple_reason(
+            "non_existent_credential_id",
+            "credential ID does not exist on the account",
+        ),
+        RejectReason::KeyIndexAlreadyInUse => simple_reason(
+            "key_index_already_in_use",
+            "credential key index is already in use",
+        ),
+        RejectReason::InvalidAccountThreshold => simple_reason(
+            "invalid_account_threshold",
+            "account threshold exceeds the number of credentials",
+        ),
+        RejectReason::InvalidCredentialKeySignThreshold => simple_reason(
+            "invalid_credential_key_sign_threshold",
+            "signature threshold exceeds the number of keys of the credential",
+        ),
+        RejectReason::InvalidEncryptedAmountTransferProof => simple_reason(
+            "invalid_encrypted_amount_transfer_proof",
+            "proof of the encrypted transfer is invalid",
+        ),
+        RejectReason::InvalidTransferToPublicProof => simple_reason(
+            "invalid_transfer_to_public_proof",
+            "proof of the transfer from the encrypted to the public balance is invalid",
+        ),
+        RejectReason::EncryptedAmountSelfTransfer { contents } => reason(
+            "encrypted_amount_self_transfer",
+            format!(
+                "account {} attempted an encrypted transfer to itself",
+                contents
+            ),
+            account_fields(contents),
+        ),
+        RejectReason::InvalidIndexOnEncryptedTransfer => simple_reason(
+            "invalid_index_on_encrypted_transfer",
+            "index of the encrypted amount is invalid",
+        ),
+        RejectReason::ZeroScheduledAmount => simple_reason(
+            "zero_scheduled_amount",
+            "transfer schedule contains a release of zero",
+        ),
+        RejectReason::NonIncreasingSchedule => simple_reason(
+            "non_increasing_schedule",
+            "release times of the transfer schedule are not strictly increasing",
+        ),
+        RejectReason::FirstScheduledReleaseExpired => simple_reason(
+            "first_scheduled_release_expired",
+            "first release of the transfer schedule is in the past",
+        ),
+        RejectReason::ScheduledSelfTransfer { contents } => reason(
+            "scheduled_self_transfer",
+            format!(
+                "account {} attempted a scheduled transfer to itself",
+                contents
+            ),
+            account_fields(contents),
+        ),
+        RejectReason::InvalidCredentials => simple_reason(
+            "invalid_credentials",
+            "at least one of the credentials is invalid",
+        ),
+        RejectReason::DuplicateCredIDs { contents } => reason(
+            "duplicate_credential_ids",
+            "credential IDs are already in use",
+            RejectReasonFields {
+                credential_ids: Some(contents.iter().map(ToString::to_string).collect()),
+                ..Default::default()
+            },
+        ),
+        RejectReason::NonExistentCredIDs { contents } => reason(
+            "non_existent_credential_ids",
+            "credential IDs do not exist on the account",
+            RejectReasonFields {
+                credential_ids: Some(contents.iter().map(ToString::to_string).collect()),
+                ..Default::default()
+            },
+        ),
+        RejectReason::RemoveFirstCredential => simple_reason(
+            "remove_first_credential",
+            "first credential of an account cannot be removed",
+        ),
+        RejectReason::CredentialHolderDidNotSign => simple_reason(
+            "credential_holder_did_not_sign",
+            "credential holder did not sign the credential deployment",
+        ),
+        RejectReason::NotAllowedMultipleCredentials => simple_reason(
+            "not_allowed_multiple_credentials",
+            "account is not allowed to have multiple credentials",
+        ),
+        RejectReason::NotAllowedToReceiveEncrypted => simple_reason(
+            "not_allowed_to_receive_encrypted",
+            "account is not allowed to receive encrypted transfers",
+        ),
+        RejectReason::NotAllowedToHandleEncrypted => simple_reason(
+            "not_allowed_to_handle_encrypted",
+            "account is not allowed to handle encrypted amounts",
+        ),
+        RejectReason::MissingBakerAddParameters => simple_reason(
+            "missing_baker_add_parameters",
+            "parameters required for adding a baker are missing",
+        ),
+        RejectReason::FinalizationRewardCommissionNotInRange => simple_reason(
+            "finalization_reward_commission_not_in_range",
+            "finalization reward commission is not in the allowed range",
+        ),
+        RejectReason::BakingRewardCommissionNotInRange => simple_reason(
+            "baking_reward_commission_not_in_range",
+            "baking reward commission is not in the allowed range",
+        ),
+        RejectReason::TransactionFeeCommissionNotInRange => simple_reason(
+            "transaction_fee_commission_not_in_range",
+            "transaction fee commission is not in the allowed range",
+        ),
+        RejectReason::AlreadyADelegator => {
+            simple_reason("already_a_delegator", "account is already a delegator")
+        }
+        RejectReason::InsufficientBalanceForDelegationStake => simple_reason(
+            "insufficient_balance_for_delegation_stake",
+            "balance of the account is insufficient for the delegation stake",
+        ),
+        RejectReason::MissingDelegationAddParameters => simple_reason(
+            "missing_delegation_add_parameters",
+            "parameters required for adding a delegator are missing",
+        ),
+        RejectReason::InsufficientDelegationStake => {
+            simple_reason("insufficient_delegation_stake", "delegation stake is zero")
+        }
+        RejectReason::DelegatorInCooldown => simple_reason(
+            "delegator_in_cooldown",
+            "delegation stake cannot be changed while a change is in cooldown",
+        ),
+        RejectReason::NotADelegator { address } => reason(
+            "not_a_delegator",
+            format!("account {} is not a delegator", address),
+            account_fields(address),
+        ),
+        RejectReason::DelegationTargetNotABaker { target } => reason(
+            "delegation_target_not_a_baker",
+            format!("delegation target {} is not a baker", target),
+            RejectReasonFields {
+                baker_id: Some(target.id.index),
+                ..Default::default()
+            },
+        ),
+        RejectReason::StakeOverMaximumThresholdForPool => simple_reason(
+            "stake_over_maximum_threshold_for_pool",
+            "stake exceeds the maximum threshold for the pool",
+        ),
+        RejectReason::PoolWouldBecomeOverDelegated => simple_reason(
+            "pool_would_become_over_delegated",
+            "delegation would exceed the capital bound of the pool",
+        ),
+        RejectReason::PoolClosed => simple_reason("pool_closed", "pool is not open for delegation"),
+        RejectReason::NonExistentTokenId(token_id) => reason(
+            "non_existent_token_id",
+            format!("token {} does not exist", token_id),
+            RejectReasonFields {
+                token_id: Some(token_id.to_string()),
+                ..Default::default()
+            },
+        ),
+        RejectReason::TokenUpdateTransactionFailed(reason_details) => reason(
+            "token_update_transaction_failed",
+            format!(
+                "token module of token {} rejected the update",
+                reason_details.token_id
+            ),
+            RejectReasonFields {
+                token_id: Some(reason_details.token_id.to_string()),
+                token_reason_type: Some(reason_details.reason_type.to_string()),
+                token_reason_details: reason_details
+                    .details
+                    .as_ref()
+                    .map(|d| hex::encode(d.as_ref())),
+                ..Default::default()
+            },
+        ),
+    }
+}
@@ -0,0 +1,146 @@
+use crate::{
+    NetworkValidator,
+    api::{
+        amount::cis2_currency,
+        cis2::{Cis2Tokens, cis2_token_from_currency},
+        error::{ApiError, ApiResult},
+        index::*,
+        query::account_address_from_identifier,
+    },
+    validate::account::{is_ccd_symbol, validate_currency},
+};
+use concordium_rust_sdk::{protocol_level_tokens::TokenId, types::hashes::TransactionHash};
+use rosetta::models::*;
+use std::str::FromStr;
+
+/// Number of transactions returned if the request doesn't specify a limit.
+const DEFAULT_LIMIT: i64 = 100;
+/// Maximum number of transactions returned in a single response.
+const MAX_LIMIT: i64 = 1000;
+
+#[derive(Clone)]
+pub struct SearchApi {
+    network_validator: NetworkValidator,
+    index: Option<TransactionIndex>,
+    cis2_tokens: Cis2Tokens,
+}
+
+impl SearchApi {
+    pub fn new(
+        network_validator: NetworkValidator,
+        index: Option<TransactionIndex>,
+        cis2_tokens: Cis2Tokens,
+    ) -> Self {
+        Self {
+            network_validator,
+            index,
+            cis2_tokens,
+        }
+    }
+
+    pub async fn search_transactions(
+        &self,
+        req: SearchTransactionsRequest,
+    ) -> ApiResult<SearchTransactionsResponse> {
+        self.network_validator
+            .validate_network_identifier(*req.network_identifier)?;
+        let index = match &self.index {
+            None => {
+                return Err(ApiError::EndpointNotEnabled(
+                    "search/transactions".to_string(),
+                ));
+            }
+            Some(i) => i,
+        };
+        if req.coin_identifier.is_some() {
+            return Err(ApiError::UnsupportedFieldPresent(
+                "coin_identifier".to_string(),
+            ));
+        }
+        let max_block = match req.max_block {
+            None => None,
+            Some(h) if h >= 0 => Some(h as u64),
+            Some(h) => return Err(ApiError::InvalidParameter("max_block".to_string(), h)),
+        };
+        let offset = match req.offset {
+            None => 0,
+            Some(o) if o >= 0 => o,
+            Some(o) => return Err(ApiError::InvalidParameter("offset".to_string(), o)),
+        };
+        let limit = match req.limit {
+            None => DEFAULT_LIMIT,
+            Some(l) if l > 0 => l.min(MAX_LIMIT),
+            Some(l) => return Err(ApiError::InvalidParameter("limit".to_string(), l)),
+        };
+
+        let mut terms = vec![];
+        if let Some(id) = req.transaction_identifier {
+            TransactionHash::from_str(&id.hash)
+                .map_err(|e| ApiError::InvalidTransactionIdentifier(id.hash.clone(), e))?;
+            terms.push(hash_term(&id.hash));
+        }
+        if let Some(id) = req.account_identifier {
+            account_address_from_identifier(&id)?;
+            terms.push(address_term(&id.address));
+        }
+        if let Some(address) = req.address {
+            account_address_from_identifier(&AccountIdentifier::new(address.clone()))?;
+            terms.push(address_term(&address));
+        }
+        if let Some(currency) = req.currency {
+            terms.push(self.currency_term(&currency)?);
+        }
+        if let Some(status) = req.status {
+            terms.push(status_term(&status));
+        }
+        if let Some(type_) = req._type {
+            terms.push(type_term(&type_));
+        }
+        if let Some(success) = req.success {
+            terms.push(success_term(success));
+        }
+
+        let (transactions, total_count) = index
+            .search(
+                &terms,
+                req.operator.unwrap_or_default(),
+                max_block,
+                offset as usize,
+                limit as usize,
+            )
+            .map_err(ApiError::InternalServerError)?;
+        let total_count = total_count as i64;
+        let next_offset = offset + transactions.len() as i64;
+        Ok(SearchTransactionsResponse {
+            transactions,
+            total_count,
+            next_offset: (next_offset < total_count).then_some(next_offset),
+        })
+    }
+
+    /// Search term of a currency that operations may be indexed with: CCD, a
+    /// protocol-level token identified by its token ID, or a tracked CIS-2
+    /// token identified by the metadata of the currency.
+    fn currency_term(&self, currency: &Currency) -> ApiResult<String> {
+        if is_ccd_symbol(currency) {
+            validate_currency(currency)?;
+            return Ok(currency_term(currency));
+        }
+        if let Some((contract, token_id)) = cis2_token_from_currency(currency)? {
+            // Only the events of tracked tokens are represented as operations.
+            let token = self
+                .cis2_tokens
+                .get(&contract, &token_id)
+                .ok_or_else(|| ApiError::NoTokensMatched(currency.symbol.clone()))?;
+            if currency.decimals != token.decimals as i32 {
+                return Err(ApiError::InvalidCurrency);
+            }
+            return Ok(currency_term(&cis2_currency(token)));
+        }
+        TokenId::try_from(currency.symbol.clone()).map_err(|_| ApiError::InvalidCurrency)?;
+        if !(0..=i32::from(u8::MAX)).contains(&currency.decimals) {
+            return Err(ApiError::InvalidCurrency);
+        }
+        Ok(currency_term(currency))
+    }
+}
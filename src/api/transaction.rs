@@ -1,16 +1,24 @@
 use crate::api::{
-    amount::amount_from_uccd,
+    amount::{amount_from_cis2_units, amount_from_token_units, amount_from_uccd},
+    cis2::{Cis2Token, Cis2Tokens},
     error::{ApiError, ApiResult},
+    reject_reason::{RejectReasonMetadata, reject_reason_metadata},
 };
 use concordium_rust_sdk::{
+    cis2,
     common::{
-        SerdeSerialize,
+        SerdeDeserialize, SerdeSerialize, cbor,
         types::{Amount, Timestamp, TransactionTime},
         upward::Upward::*,
     },
     constants::EncryptedAmountsCurve,
     encrypted_transfers::types::*,
     id::types::AccountAddress,
+    protocol_level_tokens::{
+        TokenAmount, TokenEvent, TokenEventDetails, TokenHolder, TokenId, TokenModuleEvent,
+        TokenModuleInitializationParameters, TokenModuleRef,
+    },
+    smart_contracts::common::from_bytes,
     types::*,
     v2::Upward,
 };
@@ -22,7 +30,7 @@ use std::ops::Deref;
 
 #[derive(SerdeSerialize)]
 struct TransactionRejectedMetadata {
-    reject_reason: RejectReason, // TODO replace with explicit structure
+    reject_reason: RejectReasonMetadata,
 }
 
 #[derive(SerdeSerialize)]
@@ -148,6 +156,46 @@ struct DelegationSetDelegationTargetMetadata {
     delegation_target: String,
 }
 
+pub fn open_status_to_string(status: &OpenStatus) -> &'static str {
+    match status {
+        OpenStatus::OpenForAll => "open_for_all",
+        OpenStatus::ClosedForNew => "closed_for_new",
+        OpenStatus::ClosedForAll => "closed_for_all",
+    }
+}
+
+pub fn open_status_from_string(status: &str) -> Option<OpenStatus> {
+    match status {
+        "open_for_all" => Some(OpenStatus::OpenForAll),
+        "closed_for_new" => Some(OpenStatus::ClosedForNew),
+        "closed_for_all" => Some(OpenStatus::ClosedForAll),
+        _ => None,
+    }
+}
+
+/// Format a delegation target as "passive" or "baker:<baker_id>".
+pub fn delegation_target_to_string(target: &DelegationTarget) -> String {
+    match target {
+        DelegationTarget::Passive => "passive".to_string(),
+        DelegationTarget::Baker { baker_id } => format!("baker:{}", baker_id.id),
+    }
+}
+
+/// Parse a delegation target formatted by [`delegation_target_to_string`].
+pub fn delegation_target_from_string(target: &str) -> Option<DelegationTarget> {
+    match target {
+        "passive" => Some(DelegationTarget::Passive),
+        _ => {
+            let id = target.strip_prefix("baker:")?.parse::<u64>().ok()?;
+            Some(DelegationTarget::Baker {
+                baker_id: BakerId {
+                    id: AccountIndex::from(id),
+                },
+            })
+        }
+    }
+}
+
 #[derive(SerdeSerialize)]
 struct EncryptedAmountTransferredSenderMetadata {
     new_encrypted_balance: EncryptedAmount<EncryptedAmountsCurve>,
@@ -173,17 +221,18 @@ struct TransferredToPublicMetadata {
     up_to_index: EncryptedAmountAggIndex,
 }
 
-#[derive(SerdeSerialize)]
-struct TransferredWithScheduleMetadata {
-    amounts: Vec<TimestampedAmount>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    memo: Option<Memo>,
+/// Metadata of the receiver's operation of a scheduled transfer. The same
+/// format is used for constructing scheduled transfers.
+#[derive(SerdeSerialize, SerdeDeserialize)]
+pub struct TransferWithScheduleMetadata {
+    pub amounts: Vec<TimestampedAmount>,
 }
 
-#[derive(SerdeSerialize)]
-struct TimestampedAmount {
-    timestamp: Timestamp,
-    amount_uccd: Amount,
+/// Release of a scheduled transfer.
+#[derive(SerdeSerialize, SerdeDeserialize)]
+pub struct TimestampedAmount {
+    pub timestamp: Timestamp,
+    pub amount_uccd: Amount,
 }
 
 #[derive(SerdeSerialize)]
@@ -210,6 +259,66 @@ struct AccountCreatedMetadata {
     registration_id: CredentialRegistrationID,
 }
 
+#[derive(SerdeSerialize)]
+struct TokenTransferMetadata {
+    token_id: TokenId,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    memo: Option<Memo>,
+}
+
+#[derive(SerdeSerialize)]
+struct TokenSupplyUpdateMetadata {
+    token_id: TokenId,
+}
+
+#[derive(SerdeSerialize)]
+struct TokenModuleEventMetadata {
+    token_id: TokenId,
+    event: TokenModuleEvent,
+}
+
+#[derive(SerdeSerialize)]
+struct TokenCreationMetadata {
+    token_id: TokenId,
+    token_module: TokenModuleRef,
+    decimals: u8,
+    // Decoded from the initialization parameters if possible.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    governance_account: Option<AccountAddress>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    initial_supply: Option<String>,
+}
+
+#[derive(SerdeSerialize)]
+struct ContractUpdatedMetadata {
+    receive_name: smart_contracts::OwnedReceiveName,
+    /// Hex encoded parameter of the invocation.
+    parameter: String,
+    events: Vec<smart_contracts::ContractEvent>,
+}
+
+#[derive(SerdeSerialize)]
+struct ContractInterruptedMetadata {
+    events: Vec<smart_contracts::ContractEvent>,
+}
+
+#[derive(SerdeSerialize)]
+struct ContractResumedMetadata {
+    success: bool,
+}
+
+#[derive(SerdeSerialize)]
+struct ContractUpgradedMetadata {
+    from: smart_contracts::ModuleReference,
+    to: smart_contracts::ModuleReference,
+}
+
+#[derive(SerdeSerialize)]
+struct Cis2TokenMetadata {
+    /// Symbol of the currency of the token.
+    token: String,
+}
+
 #[derive(SerdeSerialize)]
 struct ChainUpdateMetadata {
     effective_time: TransactionTime,
@@ -219,10 +328,16 @@ struct ChainUpdateMetadata {
 pub const ACCOUNT_REWARD_BAKING: &str = "baking_reward_account";
 pub const ACCOUNT_REWARD_FINALIZATION: &str = "finalization_reward_account";
 pub const ACCOUNT_ACCRUE_FOUNDATION: &str = "foundation_accrue_account";
+pub const ACCOUNT_GAS: &str = "gas_account";
 pub const ACCOUNT_ACCRUE_POOL_PREFIX: &str = "pool_accrue_account:";
 pub const ACCOUNT_CONTRACT_PREFIX: &str = "contract:";
 pub const POOL_PASSIVE: &str = "passive";
 
+pub const SUB_ACCOUNT_STAKED: &str = "staked";
+pub const SUB_ACCOUNT_COOLDOWN: &str = "cooldown";
+pub const SUB_ACCOUNT_LOCKED: &str = "locked";
+pub const SUB_ACCOUNT_AVAILABLE: &str = "available";
+
 pub const OPERATION_STATUS_OK: &str = "ok";
 pub const OPERATION_STATUS_FAIL: &str = "fail";
 
@@ -247,7 +362,7 @@ pub const OPERATION_TYPE_REMOVE_BAKER: &str = "remove_baker";
 pub const OPERATION_TYPE_TRANSFER: &str = "transfer";
 pub const OPERATION_TYPE_TRANSFER_TO_ENCRYPTED: &str = "transfer_to_encrypted";
 pub const OPERATION_TYPE_TRANSFER_TO_PUBLIC: &str = "transfer_to_public";
-pub const OPERATION_TYPE_TRANSFER_WITH_SCHEDULE: &str = "transfer_with_schedule"; // TODO is just a transfer with schedule metadata?
+pub const OPERATION_TYPE_TRANSFER_WITH_SCHEDULE: &str = "transfer_with_schedule";
 pub const OPERATION_TYPE_UPDATE_BAKER_KEYS: &str = "update_baker_keys";
 pub const OPERATION_TYPE_UPDATE_BAKER_RESTAKE_EARNINGS: &str = "update_baker_restake_earnings";
 pub const OPERATION_TYPE_UPDATE_BAKER_STAKE: &str = "update_baker_stake";
@@ -264,6 +379,16 @@ pub const OPERATION_TYPE_CONFIGURE_DELEGATION: &str = "configure_delegation";
 pub const OPERATION_TYPE_VALIDATOR_PRIMED_FOR_SUSPENSION: &str = "validator_primed_for_suspension";
 pub const OPERATION_TYPE_VALIDATOR_SUSPENDED: &str = "validator_suspended";
 pub const OPERATION_TYPE_TOKEN_UPDATE: &str = "token_update";
+pub const OPERATION_TYPE_TOKEN_TRANSFER: &str = "token_transfer";
+pub const OPERATION_TYPE_TOKEN_MINT: &str = "token_mint";
+pub const OPERATION_TYPE_TOKEN_BURN: &str = "token_burn";
+pub const OPERATION_TYPE_TOKEN_CREATION: &str = "token_creation";
+pub const OPERATION_TYPE_CONTRACT_INTERRUPTED: &str = "contract_interrupted";
+pub const OPERATION_TYPE_CONTRACT_RESUMED: &str = "contract_resumed";
+pub const OPERATION_TYPE_CONTRACT_UPGRADED: &str = "contract_upgraded";
+pub const OPERATION_TYPE_CIS2_TRANSFER: &str = "cis2_transfer";
+pub const OPERATION_TYPE_CIS2_MINT: &str = "cis2_mint";
+pub const OPERATION_TYPE_CIS2_BURN: &str = "cis2_burn";
 // Note: All operations should be returned by the `network_options` endpoint.
 // If you add a new operation type, make sure to add it there as well.
 
@@ -276,15 +401,19 @@ pub fn contract_address_string(contract_addr: &ContractAddress) -> String {
     )
 }
 
-pub fn map_transaction(info: BlockItemSummary) -> Transaction {
+pub fn map_transaction(info: BlockItemSummary, cis2_tokens: &Cis2Tokens) -> Transaction {
     let (operations, extra_metadata) = match &info.details {
         Known(BlockItemSummaryDetails::AccountTransaction(details)) => {
-            let (ops, metadata) = operations_and_metadata_from_account_transaction_details(details);
+            let (ops, metadata) =
+                operations_and_metadata_from_account_transaction_details(details, cis2_tokens);
             let mut ops_with_fee = ops.clone();
             if details.cost.micro_ccd() != 0 {
                 ops_with_fee.push(Operation {
                     operation_identifier: Box::new(OperationIdentifier::new(ops.len() as i64)),
-                    related_operations: None,
+                    // The fee is caused by the main operation of the transaction.
+                    related_operations: ops
+                        .first()
+                        .map(|op| vec![op.operation_identifier.deref().clone()]),
                     _type: OPERATION_TYPE_FEE.to_string(),
                     status: Some(OPERATION_STATUS_OK.to_string()),
                     account: Some(Box::new(AccountIdentifier::new(details.sender.to_string()))),
@@ -305,9 +434,8 @@ pub fn map_transaction(info: BlockItemSummary) -> Transaction {
             operations_and_metadata_from_chain_update_details(details),
             None,
         ),
-        Known(BlockItemSummaryDetails::TokenCreationDetails(_details)) => {
-            log::warn!("Token creation not yet supported");
-            (vec![], None)
+        Known(BlockItemSummaryDetails::TokenCreationDetails(details)) => {
+            (operations_from_token_creation_details(details), None)
         }
         Unknown(_) => {
             log::warn!(
@@ -330,6 +458,7 @@ pub fn map_transaction(info: BlockItemSummary) -> Transaction {
 
 fn operations_and_metadata_from_account_transaction_details(
     details: &AccountTransactionDetails,
+    cis2_tokens: &Cis2Tokens,
 ) -> (Vec<Operation>, Option<Result<Value, Error>>) {
     match &details.effects {
         Known(AccountTransactionEffects::None {
@@ -348,7 +477,7 @@ fn operations_and_metadata_from_account_transaction_details(
                     .as_known()
                     .map(|reject_reason| {
                         serde_json::to_value(&TransactionRejectedMetadata {
-                            reject_reason: reject_reason.clone(),
+                            reject_reason: reject_reason_metadata(reject_reason),
                         })
                         .unwrap()
                     })
@@ -388,18 +517,29 @@ fn operations_and_metadata_from_account_transaction_details(
                 }),
             )];
             if data.amount.micro_ccd() != 0 {
-                ops.push(account_transaction_operation::<Value>(
+                let mut contract_operation = account_transaction_operation::<Value>(
                     1,
                     details,
                     contract_address_string(&data.address),
                     Some(amount_from_uccd(data.amount.micro_ccd() as i128)),
                     None,
-                ));
+                );
+                add_related_operation(&mut contract_operation, &ops[0].operation_identifier);
+                ops.push(contract_operation);
             }
+            let init_id = ops[0].operation_identifier.deref().clone();
+            ops.extend(cis2_event_operations(
+                ops.len() as i64,
+                details,
+                cis2_tokens,
+                &data.address,
+                &data.events,
+                &init_id,
+            ));
             (ops, None)
         }
         Known(AccountTransactionEffects::ContractUpdateIssued { effects }) => {
-            (contract_update_operations(details, effects), None)
+            (contract_update_operations(details, effects, cis2_tokens), None)
         }
         Known(AccountTransactionEffects::AccountTransfer { amount, to }) => {
             (simple_transfer_operations(details, amount, to), None)
@@ -520,37 +660,12 @@ fn operations_and_metadata_from_account_transaction_details(
             )],
             None,
         ),
-        Known(AccountTransactionEffects::TransferredWithSchedule { to, amount }) => (
-            simple_transfer_operations(
-                details,
-                &Amount::from_micro_ccd(amount.iter().map(|(_, a)| a.micro_ccd()).sum()),
-                to,
-            ),
-            Some(serde_json::to_value(TransferredWithScheduleMetadata {
-                amounts: amount
-                    .iter()
-                    .map(|(t, a)| TimestampedAmount {
-                        timestamp: *t,
-                        amount_uccd: *a,
-                    })
-                    .collect(),
-                memo: None,
-            })),
-        ),
+        Known(AccountTransactionEffects::TransferredWithSchedule { to, amount }) => {
+            (scheduled_transfer_operations(details, to, amount), None)
+        }
         Known(AccountTransactionEffects::TransferredWithScheduleAndMemo { to, amount, memo }) => (
-            simple_transfer_operations(
-                details,
-                &Amount::from_micro_ccd(amount.iter().map(|(_, a)| a.micro_ccd()).sum()),
-                to,
-            ),
-            Some(serde_json::to_value(TransferredWithScheduleMetadata {
-                amounts: amount
-                    .iter()
-                    .map(|(t, a)| TimestampedAmount {
-                        timestamp: *t,
-                        amount_uccd: *a,
-                    })
-                    .collect(),
+            scheduled_transfer_operations(details, to, amount),
+            Some(serde_json::to_value(MemoMetadata {
                 memo: Some(memo.clone()),
             })),
         ),
@@ -700,9 +815,7 @@ fn operations_and_metadata_from_account_transaction_details(
                             Some(&BakerSetOpenStatusMetadata {
                                 baker_id: *baker_id,
                                 open_status: match open_status {
-                                    Known(OpenStatus::OpenForAll) => "open_for_all".to_string(),
-                                    Known(OpenStatus::ClosedForNew) => "closed_for_new".to_string(),
-                                    Known(OpenStatus::ClosedForAll) => "closed_for_all".to_string(),
+                                    Known(s) => open_status_to_string(s).to_string(),
                                     Unknown(_) => "unknown".to_string(),
                                 },
                             }),
@@ -873,12 +986,7 @@ fn operations_and_metadata_from_account_transaction_details(
                             None,
                             Some(&DelegationSetDelegationTargetMetadata {
                                 delegator_id: *delegator_id,
-                                delegation_target: match delegation_target {
-                                    DelegationTarget::Passive => "passive".to_string(),
-                                    DelegationTarget::Baker { baker_id } => {
-                                        format!("baker:{}", baker_id.id)
-                                    }
-                                },
+                                delegation_target: delegation_target_to_string(delegation_target),
                             }),
                         ),
                     })
@@ -886,9 +994,8 @@ fn operations_and_metadata_from_account_transaction_details(
                 .collect(),
             None,
         ),
-        Known(AccountTransactionEffects::TokenUpdate { .. }) => {
-            log::warn!("Token update not yet supported");
-            (vec![], None)
+        Known(AccountTransactionEffects::TokenUpdate { events }) => {
+            (token_event_operations(0, Some(&details.sender), events), None)
         }
         Unknown(_) => {
             log::warn!(
@@ -966,17 +1073,70 @@ fn operations_and_metadata_from_chain_update_details(details: &UpdateDetails) ->
     }]
 }
 
+fn operations_from_token_creation_details(details: &TokenCreationDetails) -> Vec<Operation> {
+    let create_plt = &details.create_plt;
+    let (governance_account, initial_supply) =
+        match cbor::cbor_decode::<TokenModuleInitializationParameters>(
+            create_plt.initialization_parameters.as_ref(),
+        ) {
+            Ok(params) => (
+                params.governance_account.map(|h| h.address),
+                params.initial_supply,
+            ),
+            Err(err) => {
+                log::warn!(
+                    "Cannot decode initialization parameters of token {}: {}",
+                    create_plt.token_id,
+                    err
+                );
+                (None, None)
+            }
+        };
+    let mut ops = vec![token_operation(
+        0,
+        OPERATION_TYPE_TOKEN_CREATION,
+        governance_account.map(|a| a.to_string()),
+        None,
+        &TokenCreationMetadata {
+            token_id: create_plt.token_id.clone(),
+            token_module: create_plt.token_module,
+            decimals: create_plt.decimals,
+            governance_account,
+            initial_supply: initial_supply.map(|a| a.value().to_string()),
+        },
+    )];
+    // Initial mints etc.
+    let creation_id = ops[0].operation_identifier.deref().clone();
+    for mut op in token_event_operations(1, None, &details.events) {
+        add_related_operation(&mut op, &creation_id);
+        ops.push(op);
+    }
+    ops
+}
+
 fn contract_update_operations(
     details: &AccountTransactionDetails,
     effects: &[Upward<ContractTraceElement>],
+    cis2_tokens: &Cis2Tokens,
 ) -> Vec<Operation> {
-    let mut ops = vec![];
-    let mut next_index = 0;
+    let mut ops: Vec<Operation> = vec![];
+    // Transfers made by a V1 contract are reported before the 'Updated' element of the
+    // invocation that made them. The identifiers of their sender operations are kept
+    // here until that element is encountered such that it can be linked to them.
+    let mut pending_transfers: Vec<(ContractAddress, OperationIdentifier)> = vec![];
+    // Transfers made by a V0 contract are reported after the 'Updated' element of the
+    // invocation that made them as the actions of a V0 contract are executed once it has
+    // returned. The identifiers of the contract operations of the most recent invocation of
+    // each V0 contract are kept here such that the transfers can be linked to them.
+    let mut v0_invocations: Vec<(ContractAddress, OperationIdentifier)> = vec![];
+    // Identifiers of the operations of interrupted invocations that haven't yet been resumed.
+    let mut interrupted: Vec<OperationIdentifier> = vec![];
     for e in effects.iter() {
+        let next_index = ops.len() as i64;
         match e {
             Known(ContractTraceElement::Updated { data }) => {
                 // TODO Adapt and use 'simple_transfer_operations'.
-                ops.push(account_transaction_operation::<Value>(
+                let instigator_operation = account_transaction_operation::<Value>(
                     next_index,
                     details,
                     match &data.instigator {
@@ -985,37 +1145,123 @@ fn contract_update_operations(
                     },
                     Some(amount_from_uccd(-(data.amount.micro_ccd() as i128))),
                     None,
-                ));
-                ops.push(account_transaction_operation::<Value>(
+                );
+                let mut contract_operation = account_transaction_operation(
                     next_index + 1,
                     details,
                     contract_address_string(&data.address),
                     Some(amount_from_uccd(data.amount.micro_ccd() as i128)),
-                    None,
+                    Some(&ContractUpdatedMetadata {
+                        receive_name: data.receive_name.clone(),
+                        parameter: hex::encode(data.message.as_ref()),
+                        events: data.events.clone(),
+                    }),
+                );
+                add_related_operation(
+                    &mut contract_operation,
+                    &instigator_operation.operation_identifier,
+                );
+                pending_transfers.retain(|(from, id)| {
+                    if *from != data.address {
+                        return true;
+                    }
+                    add_related_operation(&mut contract_operation, id);
+                    false
+                });
+                let contract_id = contract_operation.operation_identifier.deref().clone();
+                if data.contract_version == smart_contracts::WasmVersion::V0 {
+                    v0_invocations.retain(|(address, _)| *address != data.address);
+                    v0_invocations.push((data.address, contract_id.clone()));
+                }
+                ops.push(instigator_operation);
+                ops.push(contract_operation);
+                ops.extend(cis2_event_operations(
+                    next_index + 2,
+                    details,
+                    cis2_tokens,
+                    &data.address,
+                    &data.events,
+                    &contract_id,
                 ));
-                next_index += 2;
             }
             Known(ContractTraceElement::Transferred { from, amount, to }) => {
                 // TODO Adapt and use 'simple_transfer_operations'.
-                ops.push(account_transaction_operation::<Value>(
+                let mut sender_operation = account_transaction_operation::<Value>(
                     next_index,
                     details,
                     contract_address_string(from),
                     Some(amount_from_uccd(-(amount.micro_ccd() as i128))),
                     None,
-                ));
-                ops.push(account_transaction_operation::<Value>(
+                );
+                let mut receiver_operation = account_transaction_operation::<Value>(
                     next_index + 1,
                     details,
                     to.to_string(),
                     Some(amount_from_uccd(amount.micro_ccd() as i128)),
                     None,
+                );
+                add_related_operation(
+                    &mut receiver_operation,
+                    &sender_operation.operation_identifier,
+                );
+                match v0_invocations.iter().find(|(address, _)| address == from) {
+                    Some((_, contract_id)) => {
+                        add_related_operation(&mut sender_operation, contract_id)
+                    }
+                    None => pending_transfers
+                        .push((*from, sender_operation.operation_identifier.deref().clone())),
+                }
+                ops.push(sender_operation);
+                ops.push(receiver_operation);
+            }
+            Known(ContractTraceElement::Interrupted { address, events }) => {
+                let op = contract_trace_operation(
+                    next_index,
+                    details,
+                    OPERATION_TYPE_CONTRACT_INTERRUPTED,
+                    address,
+                    &ContractInterruptedMetadata {
+                        events: events.clone(),
+                    },
+                );
+                let interrupted_id = op.operation_identifier.deref().clone();
+                interrupted.push(interrupted_id.clone());
+                ops.push(op);
+                ops.extend(cis2_event_operations(
+                    next_index + 1,
+                    details,
+                    cis2_tokens,
+                    address,
+                    events,
+                    &interrupted_id,
+                ));
+            }
+            Known(ContractTraceElement::Resumed { address, success }) => {
+                let mut op = contract_trace_operation(
+                    next_index,
+                    details,
+                    OPERATION_TYPE_CONTRACT_RESUMED,
+                    address,
+                    &ContractResumedMetadata { success: *success },
+                );
+                // Invocations are resumed in the reverse order of being interrupted.
+                if let Some(id) = interrupted.pop() {
+                    add_related_operation(&mut op, &id);
+                }
+                ops.push(op);
+            }
+            Known(ContractTraceElement::Upgraded { address, from, to }) => {
+                ops.push(contract_trace_operation(
+                    next_index,
+                    details,
+                    OPERATION_TYPE_CONTRACT_UPGRADED,
+                    address,
+                    &ContractUpgradedMetadata {
+                        from: *from,
+                        to: *to,
+                    },
                 ));
-                next_index += 2;
             }
-            Known(ContractTraceElement::Interrupted { .. }) => {}
-            Known(ContractTraceElement::Resumed { .. }) => {}
-            Known(ContractTraceElement::Upgraded { .. }) => {}
             Unknown(_) => {
                 log::warn!(
                     "Unknown contract trace element in contract update effects; skipping. \
@@ -1028,6 +1274,268 @@ fn contract_update_operations(
     ops
 }
 
+/// Operations for the CIS-2 events among the events logged by the given
+/// contract, indexed from 'first_index'. Only events of tracked tokens are
+/// decoded; all other events are ignored. The operations are related to the
+/// operation with identifier 'cause' (the invocation that logged the events).
+fn cis2_event_operations(
+    first_index: i64,
+    details: &AccountTransactionDetails,
+    cis2_tokens: &Cis2Tokens,
+    contract: &ContractAddress,
+    events: &[smart_contracts::ContractEvent],
+    cause: &OperationIdentifier,
+) -> Vec<Operation> {
+    let mut ops = vec![];
+    if !cis2_tokens.is_tracked_contract(contract) {
+        return ops;
+    }
+    for e in events {
+        let Ok(event) = from_bytes::<cis2::Event>(e.as_ref()) else {
+            // Not a CIS-2 event.
+            continue;
+        };
+        let index = first_index + ops.len() as i64;
+        match event {
+            cis2::Event::Transfer {
+                token_id,
+                amount,
+                from,
+                to,
+            } => {
+                let Some(token) = cis2_tokens.get(contract, &token_id) else {
+                    continue;
+                };
+                let mut sender_operation = cis2_operation(
+                    index,
+                    details,
+                    OPERATION_TYPE_CIS2_TRANSFER,
+                    &from,
+                    &amount,
+                    true,
+                    token,
+                );
+                add_related_operation(&mut sender_operation, cause);
+                let mut receiver_operation = cis2_operation(
+                    index + 1,
+                    details,
+                    OPERATION_TYPE_CIS2_TRANSFER,
+                    &to,
+                    &amount,
+                    false,
+                    token,
+                );
+                add_related_operation(&mut receiver_operation, cause);
+                add_related_operation(
+                    &mut receiver_operation,
+                    &sender_operation.operation_identifier,
+                );
+                ops.push(sender_operation);
+                ops.push(receiver_operation);
+            }
+            cis2::Event::Mint {
+                token_id,
+                amount,
+                owner,
+            } => {
+                let Some(token) = cis2_tokens.get(contract, &token_id) else {
+                    continue;
+                };
+                let mut op = cis2_operation(
+                    index,
+                    details,
+                    OPERATION_TYPE_CIS2_MINT,
+                    &owner,
+                    &amount,
+                    false,
+                    token,
+                );
+                add_related_operation(&mut op, cause);
+                ops.push(op);
+            }
+            cis2::Event::Burn {
+                token_id,
+                amount,
+                owner,
+            } => {
+                let Some(token) = cis2_tokens.get(contract, &token_id) else {
+                    continue;
+                };
+                let mut op = cis2_operation(
+                    index,
+                    details,
+                    OPERATION_TYPE_CIS2_BURN,
+                    &owner,
+                    &amount,
+                    true,
+                    token,
+                );
+                add_related_operation(&mut op, cause);
+                ops.push(op);
+            }
+            // Events that don't affect balances.
+            cis2::Event::UpdateOperator { .. }
+            | cis2::Event::TokenMetadata { .. }
+            | cis2::Event::Unknown => {}
+        }
+    }
+    ops
+}
+
+fn cis2_operation(
+    index: i64,
+    details: &AccountTransactionDetails,
+    type_: &str,
+    address: &Address,
+    amount: &cis2::TokenAmount,
+    negate: bool,
+    token: &Cis2Token,
+) -> Operation {
+    let value = amount.0.to_string();
+    let value = if negate && value != "0" {
+        format!("-{}", value)
+    } else {
+        value
+    };
+    let mut op = account_transaction_operation(
+        index,
+        details,
+        match address {
+            Address::Account(a) => a.to_string(),
+            Address::Contract(a) => contract_address_string(a),
+        },
+        Some(amount_from_cis2_units(value, token)),
+        Some(&Cis2TokenMetadata {
+            token: token.symbol(),
+        }),
+    );
+    op._type = type_.to_string();
+    op
+}
+
+/// Operation without amount for a contract trace element that isn't a transfer.
+fn contract_trace_operation<T: SerdeSerialize>(
+    index: i64,
+    details: &AccountTransactionDetails,
+    type_: &str,
+    contract_address: &ContractAddress,
+    metadata: &T,
+) -> Operation {
+    let mut op = account_transaction_operation(
+        index,
+        details,
+        contract_address_string(contract_address),
+        None,
+        Some(metadata),
+    );
+    op._type = type_.to_string();
+    op
+}
+
+/// Operations for the events of a token update or creation, indexed from
+/// 'first_index'. Events emitted by the token module are attributed to
+/// 'sender' (absent for chain updates).
+fn token_event_operations(
+    first_index: i64,
+    sender: Option<&AccountAddress>,
+    events: &[TokenEvent],
+) -> Vec<Operation> {
+    let mut ops = vec![];
+    for e in events {
+        let index = first_index + ops.len() as i64;
+        let token_id = e.token_id.as_ref();
+        match &e.event {
+            TokenEventDetails::Transfer(transfer) => {
+                let metadata = TokenTransferMetadata {
+                    token_id: e.token_id.clone(),
+                    memo: transfer.memo.clone(),
+                };
+                let sender_operation = token_operation(
+                    index,
+                    OPERATION_TYPE_TOKEN_TRANSFER,
+                    Some(token_holder_string(&transfer.from)),
+                    Some(token_amount(token_id, &transfer.amount, true)),
+                    &metadata,
+                );
+                let mut receiver_operation = token_operation(
+                    index + 1,
+                    OPERATION_TYPE_TOKEN_TRANSFER,
+                    Some(token_holder_string(&transfer.to)),
+                    Some(token_amount(token_id, &transfer.amount, false)),
+                    &metadata,
+                );
+                receiver_operation.related_operations =
+                    Some(vec![sender_operation.operation_identifier.deref().clone()]);
+                ops.push(sender_operation);
+                ops.push(receiver_operation);
+            }
+            TokenEventDetails::Mint(mint) => ops.push(token_operation(
+                index,
+                OPERATION_TYPE_TOKEN_MINT,
+                Some(token_holder_string(&mint.target)),
+                Some(token_amount(token_id, &mint.amount, false)),
+                &TokenSupplyUpdateMetadata {
+                    token_id: e.token_id.clone(),
+                },
+            )),
+            TokenEventDetails::Burn(burn) => ops.push(token_operation(
+                index,
+                OPERATION_TYPE_TOKEN_BURN,
+                Some(token_holder_string(&burn.target)),
+                Some(token_amount(token_id, &burn.amount, true)),
+                &TokenSupplyUpdateMetadata {
+                    token_id: e.token_id.clone(),
+                },
+            )),
+            TokenEventDetails::Module(event) => ops.push(token_operation(
+                index,
+                OPERATION_TYPE_TOKEN_UPDATE,
+                sender.map(|a| a.to_string()),
+                None,
+                &TokenModuleEventMetadata {
+                    token_id: e.token_id.clone(),
+                    event: event.clone(),
+                },
+            )),
+        }
+    }
+    ops
+}
+
+fn token_operation<T: SerdeSerialize>(
+    index: i64,
+    type_: &str,
+    account_address: Option<String>,
+    amount: Option<rosetta::models::Amount>,
+    metadata: &T,
+) -> Operation {
+    Operation {
+        operation_identifier: Box::new(OperationIdentifier::new(index)),
+        related_operations: None,
+        _type: type_.to_string(),
+        status: Some(OPERATION_STATUS_OK.to_string()),
+        account: account_address.map(|a| Box::new(AccountIdentifier::new(a))),
+        amount: amount.map(Box::new),
+        coin_change: None,
+        metadata: Some(serde_json::to_value(metadata).unwrap()),
+    }
+}
+
+fn token_amount(token_id: &str, amount: &TokenAmount, negate: bool) -> rosetta::models::Amount {
+    let value = amount.value() as i128;
+    amount_from_token_units(
+        if negate { -value } else { value },
+        token_id,
+        amount.decimals(),
+    )
+}
+
+fn token_holder_string(holder: &TokenHolder) -> String {
+    match holder {
+        TokenHolder::Account { address } => address.to_string(),
+    }
+}
+
 fn simple_transfer_operations(
     details: &AccountTransactionDetails,
     amount: &Amount,
@@ -1052,6 +1560,30 @@ fn simple_transfer_operations(
     vec![sender_operation, receiver_operation]
 }
 
+/// Like a simple transfer, but with the release schedule in the metadata of the
+/// receiver's operation.
+fn scheduled_transfer_operations(
+    details: &AccountTransactionDetails,
+    to: &AccountAddress,
+    schedule: &[(Timestamp, Amount)],
+) -> Vec<Operation> {
+    let amount = Amount::from_micro_ccd(schedule.iter().map(|(_, a)| a.micro_ccd()).sum());
+    let mut ops = simple_transfer_operations(details, &amount, to);
+    ops[1].metadata = Some(
+        serde_json::to_value(TransferWithScheduleMetadata {
+            amounts: schedule
+                .iter()
+                .map(|(t, a)| TimestampedAmount {
+                    timestamp: *t,
+                    amount_uccd: *a,
+                })
+                .collect(),
+        })
+        .unwrap(),
+    );
+    ops
+}
+
 fn encrypted_transfer_operations(
     details: &AccountTransactionDetails,
     removed: &EncryptedAmountRemovedEvent,
@@ -1083,6 +1615,14 @@ fn encrypted_transfer_operations(
     vec![sender_operation, receiver_operation]
 }
 
+/// Record that 'op' is related to (i.e. caused by or causing) the operation
+/// with the given identifier. Only operations with a lower index may be related.
+pub fn add_related_operation(op: &mut Operation, id: &OperationIdentifier) {
+    op.related_operations
+        .get_or_insert_with(Vec::new)
+        .push(id.clone());
+}
+
 fn normal_account_transaction_operation<T: SerdeSerialize>(
     index: i64,
     details: &AccountTransactionDetails,
@@ -1142,6 +1682,8 @@ pub fn transaction_type_from_operation_type(type_: &str) -> ApiResult<Transactio
         OPERATION_TYPE_UPDATE_CONTRACT => Ok(TransactionType::Update),
         OPERATION_TYPE_UPDATE_CREDENTIAL_KEYS => Ok(TransactionType::UpdateCredentialKeys),
         OPERATION_TYPE_UPDATE_CREDENTIALS => Ok(TransactionType::UpdateCredentials),
+        OPERATION_TYPE_CONFIGURE_BAKER => Ok(TransactionType::ConfigureBaker),
+        OPERATION_TYPE_CONFIGURE_DELEGATION => Ok(TransactionType::ConfigureDelegation),
         _ => Err(ApiError::UnsupportedOperationType(type_.to_string())),
     }
 }
@@ -1166,7 +1708,7 @@ pub fn transaction_type_to_operation_type(type_: Option<TransactionType>) -> Str
             TransactionType::TransferToEncrypted => OPERATION_TYPE_TRANSFER_TO_ENCRYPTED,
             TransactionType::TransferToPublic => OPERATION_TYPE_TRANSFER_TO_PUBLIC,
             TransactionType::TransferWithMemo => OPERATION_TYPE_TRANSFER,
-            TransactionType::TransferWithScheduleAndMemo => OPERATION_TYPE_TRANSFER,
+            TransactionType::TransferWithScheduleAndMemo => OPERATION_TYPE_TRANSFER_WITH_SCHEDULE,
             TransactionType::TransferWithSchedule => OPERATION_TYPE_TRANSFER_WITH_SCHEDULE,
             TransactionType::Update => OPERATION_TYPE_UPDATE_CONTRACT,
             #[allow(deprecated)]
@@ -1186,3 +1728,206 @@ pub fn transaction_type_to_operation_type(type_: Option<TransactionType>) -> Str
     };
     res.to_string()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TAG_TRANSFER: u8 = 255;
+    const TAG_MINT: u8 = 254;
+    const TAG_BURN: u8 = 253;
+
+    const SENDER: AccountAddress = AccountAddress([1; 32]);
+    const HOLDER: AccountAddress = AccountAddress([2; 32]);
+
+    fn contract() -> ContractAddress {
+        ContractAddress::new(7, 0)
+    }
+
+    fn details() -> AccountTransactionDetails {
+        AccountTransactionDetails {
+            cost: Amount::from_micro_ccd(0),
+            sender: SENDER,
+            effects: Known(AccountTransactionEffects::ContractUpdateIssued { effects: vec![] }),
+        }
+    }
+
+    /// Serialize a CIS-2 event with the given tag, token ID, and (LEB128
+    /// encoded) amount followed by the given addresses.
+    fn event(
+        tag: u8,
+        token_id: &[u8],
+        amount: &[u8],
+        addresses: &[Vec<u8>],
+    ) -> smart_contracts::ContractEvent {
+        let mut bytes = vec![tag, token_id.len() as u8];
+        bytes.extend_from_slice(token_id);
+        bytes.extend_from_slice(amount);
+        for a in addresses {
+            bytes.extend_from_slice(a);
+        }
+        smart_contracts::ContractEvent::from(bytes)
+    }
+
+    fn account_bytes(addr: AccountAddress) -> Vec<u8> {
+        let mut bytes = vec![0];
+        bytes.extend_from_slice(&addr.0);
+        bytes
+    }
+
+    fn contract_bytes(addr: ContractAddress) -> Vec<u8> {
+        let mut bytes = vec![1];
+        bytes.extend_from_slice(&addr.index.to_le_bytes());
+        bytes.extend_from_slice(&addr.subindex.to_le_bytes());
+        bytes
+    }
+
+    /// Decode events logged by `contract()` with the token "01" (3 decimals)
+    /// tracked, starting at index 1 and caused by the operation at index 0.
+    fn operations(events: &[smart_contracts::ContractEvent]) -> Vec<Operation> {
+        let tokens = Cis2Tokens::parse("7_0:01:3").unwrap();
+        cis2_event_operations(
+            1,
+            &details(),
+            &tokens,
+            &contract(),
+            events,
+            &OperationIdentifier::new(0),
+        )
+    }
+
+    fn value(op: &Operation) -> &str {
+        &op.amount.as_ref().unwrap().value
+    }
+
+    fn account(op: &Operation) -> &str {
+        &op.account.as_ref().unwrap().address
+    }
+
+    fn related_indices(op: &Operation) -> Vec<i64> {
+        op.related_operations
+            .iter()
+            .flatten()
+            .map(|id| id.index)
+            .collect()
+    }
+
+    #[test]
+    fn untracked_tokens_are_skipped() {
+        let holder = account_bytes(HOLDER);
+        let ops = operations(&[
+            event(TAG_MINT, &[2], &[1], &[holder.clone()]),
+            event(TAG_MINT, &[], &[1], &[holder.clone()]),
+            event(TAG_MINT, &[1], &[5], &[holder.clone()]),
+        ]);
+        assert_eq!(ops.len(), 1);
+        assert_eq!(ops[0].operation_identifier.index, 1);
+        assert_eq!(value(&ops[0]), "5");
+
+        // Tracked token ID on a contract that isn't tracked.
+        let tokens = Cis2Tokens::parse("7_0:01:3").unwrap();
+        let ops = cis2_event_operations(
+            1,
+            &details(),
+            &tokens,
+            &ContractAddress::new(8, 0),
+            &[event(TAG_MINT, &[1], &[5], &[holder])],
+            &OperationIdentifier::new(0),
+        );
+        assert!(ops.is_empty());
+    }
+
+    #[test]
+    fn mints_are_positive_and_burns_negative() {
+        let holder = account_bytes(HOLDER);
+        let ops = operations(&[
+            event(TAG_MINT, &[1], &[0xac, 0x02], &[holder.clone()]),
+            event(TAG_BURN, &[1], &[0x7f], &[holder.clone()]),
+            event(TAG_BURN, &[1], &[0], &[holder]),
+        ]);
+        assert_eq!(ops.len(), 3);
+        assert_eq!(ops[0]._type, OPERATION_TYPE_CIS2_MINT);
+        assert_eq!(value(&ops[0]), "300");
+        assert_eq!(ops[1]._type, OPERATION_TYPE_CIS2_BURN);
+        assert_eq!(value(&ops[1]), "-127");
+        // Zero isn't negated.
+        assert_eq!(value(&ops[2]), "0");
+        for (i, op) in ops.iter().enumerate() {
+            assert_eq!(op.operation_identifier.index, i as i64 + 1);
+            assert_eq!(account(op), HOLDER.to_string());
+            assert_eq!(related_indices(op), vec![0]);
+            let currency = &op.amount.as_ref().unwrap().currency;
+            assert_eq!(currency.symbol, "contract:7_0:01");
+            assert_eq!(currency.decimals, 3);
+        }
+    }
+
+    #[test]
+    fn transfers_are_sender_and_receiver_pairs() {
+        let receiver = ContractAddress::new(9, 1);
+        let ops = operations(&[event(
+            TAG_TRANSFER,
+            &[1],
+            &[0x0a],
+            &[account_bytes(HOLDER), contract_bytes(receiver)],
+        )]);
+        assert_eq!(ops.len(), 2);
+        let (sender_op, receiver_op) = (&ops[0], &ops[1]);
+        assert!(
+            ops.iter()
+                .all(|op| op._type == OPERATION_TYPE_CIS2_TRANSFER)
+        );
+        assert_eq!(sender_op.operation_identifier.index, 1);
+        assert_eq!(account(sender_op), HOLDER.to_string());
+        assert_eq!(value(sender_op), "-10");
+        assert_eq!(related_indices(sender_op), vec![0]);
+        assert_eq!(receiver_op.operation_identifier.index, 2);
+        assert_eq!(account(receiver_op), contract_address_string(&receiver));
+        assert_eq!(value(receiver_op), "10");
+        assert_eq!(related_indices(receiver_op), vec![0, 1]);
+    }
+
+    #[test]
+    fn amounts_beyond_u64_are_rendered_in_full() {
+        // 2^64 (one above the maximum u64).
+        let mut amount = vec![0x80; 9];
+        amount.push(0x02);
+        let holder = account_bytes(HOLDER);
+        let ops = operations(&[
+            event(TAG_MINT, &[1], &amount, &[holder.clone()]),
+            event(TAG_BURN, &[1], &amount, &[holder.clone()]),
+        ]);
+        assert_eq!(value(&ops[0]), "18446744073709551616");
+        assert_eq!(value(&ops[1]), "-18446744073709551616");
+
+        // The largest amount allowed by CIS-2 (2^256 - 1) is 37 bytes long.
+        let mut amount = vec![0xff; 36];
+        amount.push(0x0f);
+        let ops = operations(&[event(TAG_MINT, &[1], &amount, &[holder])]);
+        assert_eq!(
+            value(&ops[0]),
+            "115792089237316195423570985008687907853269984665640564039457584007913129639935"
+        );
+    }
+
+    #[test]
+    fn malformed_events_are_skipped() {
+        let holder = account_bytes(HOLDER);
+        // Amount longer than 37 bytes.
+        let mut too_long = vec![0x80; 37];
+        too_long.push(0x01);
+        let ops = operations(&[
+            // Amount with the continuation bit set on the last byte.
+            event(TAG_MINT, &[1], &[0x80], &[]),
+            event(TAG_MINT, &[1], &too_long, &[holder.clone()]),
+            // Transfer without receiver.
+            event(TAG_TRANSFER, &[1], &[1], &[holder.clone()]),
+            // Not a CIS-2 event.
+            smart_contracts::ContractEvent::from(vec![]),
+            event(TAG_MINT, &[1], &[1], &[holder]),
+        ]);
+        assert_eq!(ops.len(), 1);
+        assert_eq!(ops[0].operation_identifier.index, 1);
+        assert_eq!(value(&ops[0]), "1");
+    }
+}
@@ -1,6 +1,9 @@
 use crate::{
     AccountApi,
-    api::{block::BlockApi, construction::ConstructionApi},
+    api::{
+        block::BlockApi, call::CallApi, construction::ConstructionApi, events::EventsApi,
+        mempool::MempoolApi, search::SearchApi,
+    },
 };
 use rosetta::models::*;
 use serde::Serialize;
@@ -42,6 +45,35 @@ pub async fn block_transaction(
     to_json(api.block_transaction(req).await)
 }
 
+pub async fn mempool(api: MempoolApi, req: NetworkRequest) -> Result<impl Reply, Rejection> {
+    to_json(api.mempool(req).await)
+}
+
+pub async fn mempool_transaction(
+    api: MempoolApi,
+    req: MempoolTransactionRequest,
+) -> Result<impl Reply, Rejection> {
+    to_json(api.mempool_transaction(req).await)
+}
+
+pub async fn search_transactions(
+    api: SearchApi,
+    req: SearchTransactionsRequest,
+) -> Result<impl Reply, Rejection> {
+    to_json(api.search_transactions(req).await)
+}
+
+pub async fn events_blocks(
+    api: EventsApi,
+    req: EventsBlocksRequest,
+) -> Result<impl Reply, Rejection> {
+    to_json(api.events_blocks(req).await)
+}
+
+pub async fn call(api: CallApi, req: CallRequest) -> Result<impl Reply, Rejection> {
+    to_json(api.call(req).await)
+}
+
 pub async fn construction_preprocess(
     api: ConstructionApi,
     req: ConstructionPreprocessRequest,
@@ -1,6 +1,6 @@
-use crate::api::error::ApiError;
+use crate::api::{error::ApiError, reject_reason::RejectReasonMetadata};
 use rosetta::models::*;
-use serde_json::{Map, Value};
+use serde_json::{Map, Value, json};
 use warp::{Rejection, Reply, http::StatusCode, reply};
 
 pub async fn handle_rejection(rej: Rejection) -> Result<impl Reply, Rejection> {
@@ -16,13 +16,17 @@ pub async fn handle_rejection(rej: Rejection) -> Result<impl Reply, Rejection> {
     //                       * network identifier
     //                       * block identifier
     //                       * account identifier
+    //                       * sub-account identifier
     //                       * amount/currency
     //                       * signature
     //                       * encoded payload
     //                       * signed transaction
     //                       * construction options
+    //                       * query parameter
+    //                       * call parameters
     //                 1300: unsupported field value
     //                       * operation type
+    //                       * call method
     //                 1400: inconsistent value
     //                       * operations (unexpected number, non-opposite amounts)
     //  2000 -  2999: identifier not resolved
@@ -31,8 +35,13 @@ pub async fn handle_rejection(rej: Rejection) -> Result<impl Reply, Rejection> {
     //                       * block identifier
     //                       * transaction hash
     //                       * account identifier
+    //                       * contract address
+    //                       * pool
+    //                       * token
     //                 2100: multiple matches <value>
     //                       * block identifier
+    //  3000 -  3999: unavailable
+    //                 3000: endpoint not enabled <name>
     //  9000 -  9999: internal error
     //                 9000: JSON encoding failed
     // 10000 - 19999: proxy error
@@ -69,12 +78,12 @@ pub async fn handle_rejection(rej: Rejection) -> Result<impl Reply, Rejection> {
                     )),
                     StatusCode::BAD_REQUEST,
                 ),
-                ApiError::InvalidBlockTransactionRequest => reply::with_status(
+                ApiError::InvalidSubAccountAddress(addr) => reply::with_status(
                     reply::json(&invalid_input_invalid_value_or_identifier_error(
-                        Some("block transaction request".to_string()),
+                        Some("sub-account address".to_string()),
                         None,
-                        None,
-                        Some("invalid request".to_string()),
+                        Some(addr.clone()),
+                        Some("unknown sub-account".to_string()),
                     )),
                     StatusCode::BAD_REQUEST,
                 ),
@@ -182,6 +191,33 @@ pub async fn handle_rejection(rej: Rejection) -> Result<impl Reply, Rejection> {
                     )),
                     StatusCode::BAD_REQUEST,
                 ),
+                ApiError::InvalidOperationMetadata(type_, msg) => reply::with_status(
+                    reply::json(&invalid_input_invalid_value_or_identifier_error(
+                        Some("operation metadata".to_string()),
+                        Some(type_),
+                        None,
+                        Some(msg),
+                    )),
+                    StatusCode::BAD_REQUEST,
+                ),
+                ApiError::InvalidParameter(name, value) => reply::with_status(
+                    reply::json(&invalid_input_invalid_value_or_identifier_error(
+                        Some(name.clone()),
+                        None,
+                        Some(value.to_string()),
+                        None,
+                    )),
+                    StatusCode::BAD_REQUEST,
+                ),
+                ApiError::InvalidCallParameters(method, msg) => reply::with_status(
+                    reply::json(&invalid_input_invalid_value_or_identifier_error(
+                        Some("call parameters".to_string()),
+                        None,
+                        None,
+                        Some(format!("method '{}': {}", method, msg)),
+                    )),
+                    StatusCode::BAD_REQUEST,
+                ),
                 ApiError::UnsupportedOperationType(name) => reply::with_status(
                     reply::json(&invalid_input_unsupported_value_error(
                         Some("operation type".to_string()),
@@ -189,6 +225,13 @@ pub async fn handle_rejection(rej: Rejection) -> Result<impl Reply, Rejection> {
                     )),
                     StatusCode::BAD_REQUEST,
                 ),
+                ApiError::UnsupportedCallMethod(name) => reply::with_status(
+                    reply::json(&invalid_input_unsupported_value_error(
+                        Some("call method".to_string()),
+                        Some(name.clone()),
+                    )),
+                    StatusCode::BAD_REQUEST,
+                ),
                 ApiError::InconsistentOperations(err) => reply::with_status(
                     reply::json(&invalid_input_inconsistent_value_error(
                         Some("operations".to_string()),
@@ -220,12 +263,34 @@ pub async fn handle_rejection(rej: Rejection) -> Result<impl Reply, Rejection> {
                     ))),
                     StatusCode::NOT_FOUND,
                 ),
+                ApiError::NoContractsMatched => reply::with_status(
+                    reply::json(&identifier_not_resolved_no_matches_error(Some(
+                        "contract_address".to_string(),
+                    ))),
+                    StatusCode::NOT_FOUND,
+                ),
+                ApiError::NoPoolsMatched => reply::with_status(
+                    reply::json(&identifier_not_resolved_no_matches_error(Some(
+                        "pool".to_string(),
+                    ))),
+                    StatusCode::NOT_FOUND,
+                ),
+                ApiError::NoTokensMatched(symbol) => reply::with_status(
+                    reply::json(&identifier_not_resolved_no_token_matches_error(Some(
+                        symbol.clone(),
+                    ))),
+                    StatusCode::NOT_FOUND,
+                ),
                 ApiError::MultipleBlocksMatched => reply::with_status(
                     reply::json(&identifier_not_resolved_multiple_matches_error(Some(
                         "block_identifier".to_string(),
                     ))),
                     StatusCode::NOT_FOUND,
                 ),
+                ApiError::EndpointNotEnabled(name) => reply::with_status(
+                    reply::json(&unavailable_endpoint_not_enabled_error(Some(name.clone()))),
+                    StatusCode::NOT_IMPLEMENTED,
+                ),
                 // We explicitly ignore the error message as it should not be passed to the user
                 ApiError::InternalServerError(_) => reply::with_status(
                     reply::json(&internal_server_error()),
@@ -235,6 +300,10 @@ pub async fn handle_rejection(rej: Rejection) -> Result<impl Reply, Rejection> {
                     reply::json(&proxy_client_rpc_error(Some(err.to_string()))),
                     StatusCode::INTERNAL_SERVER_ERROR,
                 ),
+                ApiError::ContractInvocationRejected(reason) => reply::with_status(
+                    reply::json(&proxy_transaction_rejected_error(reason.as_deref())),
+                    StatusCode::BAD_REQUEST,
+                ),
             })
         }
     }
@@ -338,6 +407,16 @@ pub fn identifier_not_resolved_no_matches_error(identifier_type: Option<String>)
     }
 }
 
+pub fn identifier_not_resolved_no_token_matches_error(symbol: Option<String>) -> Error {
+    Error {
+        details: key_value_pairs(&[
+            key_value_pair("type", Some("currency".to_string())),
+            key_value_pair("symbol", symbol),
+        ]),
+        ..identifier_not_resolved_no_matches_error(None)
+    }
+}
+
 pub fn identifier_not_resolved_multiple_matches_error(identifier_type: Option<String>) -> Error {
     Error {
         code: 2100,
@@ -348,6 +427,18 @@ pub fn identifier_not_resolved_multiple_matches_error(identifier_type: Option<St
     }
 }
 
+pub fn unavailable_endpoint_not_enabled_error(endpoint: Option<String>) -> Error {
+    Error {
+        code: 3000,
+        message: "unavailable: endpoint is not enabled".to_string(),
+        description: Some(
+            "The endpoint has not been enabled in the configuration of the server.".to_string(),
+        ),
+        retriable: false,
+        details: key_value_pairs(&[key_value_pair("endpoint", endpoint)]),
+    }
+}
+
 pub fn proxy_client_rpc_error(err: Option<String>) -> Error {
     Error {
         code: 10000,
@@ -370,6 +461,18 @@ pub fn proxy_client_query_error(err: Option<String>) -> Error {
     }
 }
 
+pub fn proxy_transaction_rejected_error(reject_reason: Option<&RejectReasonMetadata>) -> Error {
+    Error {
+        code: 10200,
+        message: "proxy error: transaction rejected".to_string(),
+        description: Some(
+            "The node rejected the transaction when simulating its execution.".to_string(),
+        ),
+        retriable: false,
+        details: reject_reason.map(|r| json!({ "reject_reason": r })),
+    }
+}
+
 pub fn internal_server_error() -> Error {
     Error {
         code: 9100,
@@ -7,8 +7,18 @@ mod version;
 
 use crate::{
     api::{
-        account::AccountApi, block::BlockApi, construction::ConstructionApi, network::NetworkApi,
-        query::QueryHelper,
+        account::AccountApi,
+        block::BlockApi,
+        cache::BlockCache,
+        call::CallApi,
+        cis2::Cis2Tokens,
+        construction::ConstructionApi,
+        events::EventsApi,
+        index::{BlockEventLog, TransactionIndex, open_database},
+        mempool::{MempoolApi, MempoolTracker},
+        network::NetworkApi,
+        query::{HeadMode, QueryHelper},
+        search::SearchApi,
     },
     validate::{account::AccountValidator, network::NetworkValidator},
 };
@@ -17,6 +27,7 @@ use clap::Parser;
 use concordium_rust_sdk::v2::{Client, Endpoint};
 use env_logger::{Builder, Env};
 use rosetta::models::NetworkIdentifier;
+use std::path::PathBuf;
 
 #[derive(Parser)]
 #[clap(
@@ -55,6 +66,70 @@ struct Args {
         default_value = "20000"
     )]
     grpc_port: u16,
+    #[clap(
+        long = "index-path",
+        env = "CONCORDIUM_ROSETTA_INDEX_PATH",
+        help = "Path of the database file of the transaction index. If provided, the server \
+                indexes all finalized blocks in the background and serves the Indexer API from \
+                the database. The file is created if it doesn't already exist."
+    )]
+    index_path: Option<PathBuf>,
+    #[clap(
+        long = "block-events-path",
+        env = "CONCORDIUM_ROSETTA_BLOCK_EVENTS_PATH",
+        help = "Path of the database file of the block event log. If provided, the server logs \
+                the blocks of the chain in the background and serves the Events API from the \
+                database. The log is independent of the transaction index, which is only \
+                populated if '--index-path' is provided. If both paths are the same, the index \
+                and log are stored in the same file. The file is created if it doesn't already \
+                exist."
+    )]
+    block_events_path: Option<PathBuf>,
+    #[clap(
+        long = "cis2-tokens",
+        env = "CONCORDIUM_ROSETTA_CIS2_TOKENS",
+        help = "Comma separated list of CIS-2 tokens whose events are represented as operations. \
+                Each token is given in the format '<index>_<subindex>:<token_id>:<decimals>' \
+                where '<index>_<subindex>' is the address of the token contract, '<token_id>' \
+                is the hex encoded ID of the token, and '<decimals>' is the number of decimals \
+                of the token as given by its metadata."
+    )]
+    cis2_tokens: Option<String>,
+    #[clap(
+        long = "max-block-transactions",
+        env = "CONCORDIUM_ROSETTA_MAX_BLOCK_TRANSACTIONS",
+        help = "Number of transactions above which '/block' only returns the identifiers of the \
+                transactions of a block in 'other_transactions' instead of the full transactions. \
+                If not provided, all transactions are always returned in full."
+    )]
+    max_block_transactions: Option<u64>,
+    #[clap(
+        long = "head",
+        env = "CONCORDIUM_ROSETTA_HEAD",
+        help = "The block that is considered the current head of the chain: 'finalized' for the \
+                last finalized block or 'best' for the best block. With 'best', the Data API \
+                serves blocks that aren't yet finalized and the block event log removes blocks \
+                that are abandoned in favor of another branch.",
+        default_value = "finalized"
+    )]
+    head: HeadMode,
+    #[clap(
+        long = "cache-max-entries",
+        env = "CONCORDIUM_ROSETTA_CACHE_MAX_ENTRIES",
+        help = "Maximum number of entries (block responses, block infos, and block hashes) in the \
+                cache of data derived from finalized blocks. The cache is disabled by default (or \
+                if set to 0).",
+        default_value = "0"
+    )]
+    cache_max_entries: usize,
+    #[clap(
+        long = "cache-max-memory-mb",
+        env = "CONCORDIUM_ROSETTA_CACHE_MAX_MEMORY_MB",
+        help = "Maximum estimated memory usage in MiB of the cache of data derived from finalized \
+                blocks. Set to 0 to disable the cache.",
+        default_value = "256"
+    )]
+    cache_max_memory_mb: usize,
 }
 
 #[tokio::main]
@@ -79,22 +154,90 @@ async fn main() -> Result<()> {
         network: args.network,
         sub_network_identifier: None,
     });
+    let cis2_tokens = match args.cis2_tokens {
+        None => Cis2Tokens::default(),
+        Some(s) => Cis2Tokens::parse(&s).context("Invalid value of '--cis2-tokens'.")?,
+    };
     let account_validator = AccountValidator {};
-    let query_helper = QueryHelper::new(client);
+    let cache = BlockCache::new(
+        args.cache_max_entries,
+        args.cache_max_memory_mb.saturating_mul(1024 * 1024),
+    );
+    tokio::spawn(cache.clone().report_statistics());
+    let query_helper = QueryHelper::new(client, args.head, cache);
     let network_api = NetworkApi::new(network_validator.clone(), query_helper.clone());
     let account_api = AccountApi::new(
         account_validator.clone(),
         network_validator.clone(),
         query_helper.clone(),
+        cis2_tokens.clone(),
+    );
+    let block_api = BlockApi::new(
+        network_validator.clone(),
+        query_helper.clone(),
+        cis2_tokens.clone(),
+        args.max_block_transactions,
+    );
+    let call_api = CallApi::new(network_validator.clone(), query_helper.clone());
+    let mempool_tracker = MempoolTracker::default();
+    tokio::spawn(mempool_tracker.clone().follow(query_helper.clone()));
+    let mempool_api = MempoolApi::new(
+        network_validator.clone(),
+        query_helper.clone(),
+        mempool_tracker.clone(),
+        cis2_tokens.clone(),
+    );
+    let construction_api = ConstructionApi::new(
+        network_validator.clone(),
+        query_helper.clone(),
+        mempool_tracker,
+    );
+
+    // Open transaction index and block event log and start populating them in the
+    // background.
+    let index_db = args.index_path.as_ref().map(open_database).transpose()?;
+    let block_events_db = match &args.block_events_path {
+        None => None,
+        // The database file can only be opened once.
+        Some(path) if args.index_path.as_ref() == Some(path) => index_db.clone(),
+        Some(path) => Some(open_database(path)?),
+    };
+    let transaction_index = match index_db {
+        None => None,
+        Some(db) => {
+            let index = TransactionIndex::new(db)?;
+            tokio::spawn(
+                index
+                    .clone()
+                    .follow(block_api.clone(), query_helper.clone()),
+            );
+            Some(index)
+        }
+    };
+    let block_event_log = match block_events_db {
+        None => None,
+        Some(db) => {
+            let log = BlockEventLog::new(db)?;
+            tokio::spawn(log.clone().follow(query_helper.clone()));
+            Some(log)
+        }
+    };
+    let search_api = SearchApi::new(
+        network_validator.clone(),
+        transaction_index,
+        cis2_tokens.clone(),
     );
-    let block_api = BlockApi::new(network_validator.clone(), query_helper.clone());
-    let construction_api = ConstructionApi::new(network_validator.clone(), query_helper.clone());
+    let events_api = EventsApi::new(network_validator.clone(), block_event_log);
 
     // Configure and start web server.
     warp::serve(route::root(
         network_api,
         account_api,
         block_api,
+        mempool_api,
+        search_api,
+        events_api,
+        call_api,
         construction_api,
     ))
     .run(([0, 0, 0, 0], args.port))
@@ -4,7 +4,10 @@ use warp::{Filter, Rejection, Reply};
 
 use crate::{
     AccountApi, ConstructionApi,
-    api::{block::BlockApi, network::NetworkApi},
+    api::{
+        block::BlockApi, call::CallApi, events::EventsApi, mempool::MempoolApi,
+        network::NetworkApi, search::SearchApi,
+    },
     handler,
     handler_error::handle_rejection,
 };
@@ -66,6 +69,51 @@ fn block_transaction(
         .and_then(handler::block_transaction)
 }
 
+fn mempool_(api: MempoolApi) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    warp::path::end()
+        .and(with_mempool_api(api))
+        .and(warp::body::json())
+        .and_then(handler::mempool)
+}
+
+fn mempool_transaction(
+    api: MempoolApi,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    warp::path("transaction")
+        .and(warp::path::end())
+        .and(with_mempool_api(api))
+        .and(warp::body::json())
+        .and_then(handler::mempool_transaction)
+}
+
+fn search_transactions(
+    api: SearchApi,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    warp::path("transactions")
+        .and(warp::path::end())
+        .and(with_search_api(api))
+        .and(warp::body::json())
+        .and_then(handler::search_transactions)
+}
+
+fn events_blocks(
+    api: EventsApi,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    warp::path("blocks")
+        .and(warp::path::end())
+        .and(with_events_api(api))
+        .and(warp::body::json())
+        .and_then(handler::events_blocks)
+}
+
+fn call(api: CallApi) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    warp::path("call")
+        .and(warp::path::end())
+        .and(with_call_api(api))
+        .and(warp::body::json())
+        .and_then(handler::call)
+}
+
 fn construction_preprocess(
     api: ConstructionApi,
 ) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
@@ -152,6 +200,18 @@ fn block(api: BlockApi) -> impl Filter<Extract = (impl Reply,), Error = Rejectio
     warp::path("block").and(block_(api.clone()).or(block_transaction(api)))
 }
 
+fn mempool(api: MempoolApi) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    warp::path("mempool").and(mempool_(api.clone()).or(mempool_transaction(api)))
+}
+
+fn search(api: SearchApi) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    warp::path("search").and(search_transactions(api))
+}
+
+fn events(api: EventsApi) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    warp::path("events").and(events_blocks(api))
+}
+
 fn construction(
     api: ConstructionApi,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
@@ -170,6 +230,10 @@ pub fn root(
     network_api: NetworkApi,
     account_api: AccountApi,
     block_api: BlockApi,
+    mempool_api: MempoolApi,
+    search_api: SearchApi,
+    events_api: EventsApi,
+    call_api: CallApi,
     construction_api: ConstructionApi,
 ) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
     warp::post()
@@ -177,6 +241,10 @@ pub fn root(
             network(network_api)
                 .or(account(account_api))
                 .or(block(block_api))
+                .or(mempool(mempool_api))
+                .or(search(search_api))
+                .or(events(events_api))
+                .or(call(call_api))
                 .or(construction(construction_api)),
         )
         .with(warp::log("concordium_rosetta::route"))
@@ -199,6 +267,28 @@ fn with_block_api(api: BlockApi) -> impl Filter<Extract = (BlockApi,), Error = I
     warp::any().map(move || api.clone())
 }
 
+fn with_mempool_api(
+    api: MempoolApi,
+) -> impl Filter<Extract = (MempoolApi,), Error = Infallible> + Clone {
+    warp::any().map(move || api.clone())
+}
+
+fn with_search_api(
+    api: SearchApi,
+) -> impl Filter<Extract = (SearchApi,), Error = Infallible> + Clone {
+    warp::any().map(move || api.clone())
+}
+
+fn with_events_api(
+    api: EventsApi,
+) -> impl Filter<Extract = (EventsApi,), Error = Infallible> + Clone {
+    warp::any().map(move || api.clone())
+}
+
+fn with_call_api(api: CallApi) -> impl Filter<Extract = (CallApi,), Error = Infallible> + Clone {
+    warp::any().map(move || api.clone())
+}
+
 fn with_construction_api(
     api: ConstructionApi,
 ) -> impl Filter<Extract = (ConstructionApi,), Error = Infallible> + Clone {
@@ -1,4 +1,8 @@
-use crate::api::error::{ApiError, ApiResult};
+use crate::api::{
+    cis2::cis2_token_from_currency,
+    error::{ApiError, ApiResult},
+};
+use concordium_rust_sdk::protocol_level_tokens::TokenId;
 use rosetta::models::Currency;
 
 #[derive(Clone)]
@@ -13,10 +17,25 @@ impl AccountValidator {
 pub fn validate_currencies(currencies: Option<Vec<Currency>>) -> ApiResult<()> {
     match currencies {
         None => Ok(()),
-        Some(cs) => cs.iter().try_for_each(self::validate_currency),
+        Some(cs) => cs.iter().try_for_each(self::validate_balance_currency),
     }
 }
 
+/// Validate a currency of an account balance: Either CCD, a CIS-2 token
+/// identified by the metadata of the currency, or a protocol-level token
+/// identified by its token ID. The decimals of a protocol-level token are
+/// validated once the token has been resolved.
+fn validate_balance_currency(c: &Currency) -> ApiResult<()> {
+    if is_ccd_symbol(c) {
+        return validate_currency(c);
+    }
+    if cis2_token_from_currency(c)?.is_some() {
+        return Ok(());
+    }
+    TokenId::try_from(c.symbol.clone()).map_err(|_| ApiError::InvalidCurrency)?;
+    Ok(())
+}
+
 pub fn validate_currency(c: &Currency) -> ApiResult<()> {
     if !is_valid_currency(c) {
         return Err(ApiError::InvalidCurrency);
@@ -24,6 +43,10 @@ pub fn validate_currency(c: &Currency) -> ApiResult<()> {
     Ok(())
 }
 
+pub fn is_ccd_symbol(c: &Currency) -> bool {
+    c.symbol == *"CCD"
+}
+
 fn is_valid_currency(c: &Currency) -> bool {
-    c.symbol == *"CCD" && c.decimals == 6
+    is_ccd_symbol(c) && c.decimals == 6
 }